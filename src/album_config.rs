@@ -0,0 +1,158 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+//! The album config as written in the file, before it's turned into [`AlbumTags`](crate::album_tags::AlbumTags).
+//!
+//! The doc comments on these types end up in the JSON Schema printed by
+//! `riptagger schema`, so they're written for someone editing a config.
+
+use schemars::generate::SchemaSettings;
+use schemars::transform::transform_subschemas;
+use schemars::{JsonSchema, Schema};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::album_tags::TrackNumbering;
+use crate::settings::SettingsConfig;
+
+/// The config format version this riptagger reads.
+pub const CONFIG_VERSION: i64 = 1;
+
+/// A string, or an array of strings.
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(s) => vec![s],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+/// A track, with values that replace the album's for this track.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TrackConfig {
+    /// The track's title.
+    #[schemars(required, with = "String")]
+    pub title: Option<String>,
+    /// The track's artist, when it isn't the album's.
+    pub artist: Option<String>,
+    pub composer: Option<String>,
+    pub year: Option<i64>,
+    /// One genre, or several.
+    pub genre: Option<OneOrMany>,
+}
+
+/// A disc of a multi-disc album, with its own tracks.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DiscConfig {
+    /// The disc's title, such as "Live at the Roundhouse".
+    pub subtitle: Option<String>,
+    /// Cover picture for this disc's tracks in place of the album's, relative to this file.
+    pub picture: Option<String>,
+    /// Track titles in order. Use either this or `track` tables.
+    pub tracks: Option<Vec<String>>,
+    /// Tracks with their own values. Use either this or `tracks`.
+    pub track: Option<Vec<TrackConfig>>,
+}
+
+/// A side of a vinyl record, with its own tracks.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SideConfig {
+    /// Prefix of the side's track positions. Sides are named A to Z, then AA, AB, ... in order unless set.
+    pub name: Option<String>,
+    /// Track titles in order. Use either this or `track` tables.
+    pub tracks: Option<Vec<String>>,
+    /// Tracks with their own values. Use either this or `tracks`.
+    pub track: Option<Vec<TrackConfig>>,
+}
+
+/// A riptagger album config.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "riptagger album config")]
+pub struct AlbumConfig {
+    /// Schema the editor checks the file against, riptagger ignores it.
+    #[serde(rename = "$schema")]
+    pub schema: Option<String>,
+    /// A config this one builds on, such as one with an artist's defaults.
+    /// Relative to this file. Its values are used where this config doesn't set them.
+    pub extends: Option<String>,
+    /// Configs whose values are used where this config doesn't set them, taking
+    /// precedence over `extends` and over the files before them. Relative to this file.
+    pub include: Option<Vec<String>>,
+    /// Version of the config format, 1 when omitted.
+    #[schemars(range(min = 1, max = 1))]
+    pub version: Option<i64>,
+    pub album: Option<String>,
+    pub artist: Option<String>,
+    /// Defaults to `artist`, set it when tracks have different artists.
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    /// Mark the album as a compilation of various artists.
+    pub compilation: Option<bool>,
+    pub year: Option<i64>,
+    /// One genre, or several.
+    pub genre: Option<OneOrMany>,
+    /// Cover picture for every track, relative to this file. When omitted, a
+    /// cover, folder or front picture in the album directory is used.
+    pub picture: Option<String>,
+    /// Track titles in order. Use either this or `track` tables.
+    pub tracks: Option<Vec<String>>,
+    /// Tracks with their own values. Use either this or `tracks`.
+    pub track: Option<Vec<TrackConfig>>,
+    /// Discs with their own tracks, in place of `tracks` or `track`.
+    pub disc: Option<Vec<DiscConfig>>,
+    /// Vinyl sides with their own tracks, in place of `tracks`, `track` or `disc`.
+    pub side: Option<Vec<SideConfig>>,
+    /// How many sides make a record, 2 when omitted.
+    #[schemars(range(min = 1))]
+    pub sides_per_disc: Option<i64>,
+    /// Field the vinyl positions of `side` tracks are written to, VINYLTRACK when omitted.
+    pub position_tag: Option<String>,
+    /// Number of discs when tracks are listed with `tracks` or `track`.
+    #[schemars(range(min = 1))]
+    pub disc_total: Option<i64>,
+    /// Number of tracks on each disc, when tracks are listed with `tracks` or `track`.
+    pub tracks_per_disc: Option<Vec<i64>>,
+    /// Number tracks across the whole album, or restart on each disc.
+    pub track_numbering: Option<TrackNumbering>,
+    /// Settings for this album, over those of the user config.
+    pub settings: Option<SettingsConfig>,
+}
+
+/// The JSON Schema of the album config, for editors to complete and check configs with.
+pub fn album_config_schema() -> serde_json::Value {
+    // draft 7 is the newest draft editors widely support
+    SchemaSettings::draft07()
+        .with_transform(remove_null)
+        .into_generator()
+        .into_root_schema_for::<AlbumConfig>()
+        .to_value()
+}
+
+/// Options are schemas that also allow null, but TOML has no null: a key
+/// that isn't set is left out.
+fn remove_null(schema: &mut Schema) {
+    let is_null = |s: &Value| s.get("type").is_some_and(|t| t == "null");
+    if let Some(Value::Array(types)) = schema.get_mut("type") {
+        types.retain(|t| t != "null");
+        if let [only] = types.as_slice() {
+            let only = only.clone();
+            schema.insert(String::from("type"), only);
+        }
+    }
+    if let Some(Value::Array(variants)) = schema.get_mut("anyOf") {
+        variants.retain(|v| !is_null(v));
+    }
+    transform_subschemas(&mut remove_null, schema);
+}
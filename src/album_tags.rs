@@ -1,43 +1,118 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 use std::fmt;
-use std::path::Path;
-use toml::Table;
+use toml::{Table, Value};
 
-use crate::toml_helpers::{get_i64_array, get_i64_value, get_single_or_array_string, get_string_array, get_string_value};
+use serde::Deserialize;
+use schemars::JsonSchema;
+
+use crate::album_config::{AlbumConfig, DiscConfig, SideConfig, TrackConfig, CONFIG_VERSION};
 use crate::config::ConfigError;
-use crate::fs_utils::get_current_directory;
-use crate::TrackTags;
+use crate::fs_utils::expand_home;
+use crate::track_tags::TrackTags;
+use crate::validation::validate_album_table;
+
+/// Values from a `[[track]]` table that replace the album's for that track.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackOverrides {
+    pub artist_name: Option<String>,
+    pub composer: Option<String>,
+    pub year: Option<i64>,
+    pub genre: Option<Vec<String>>,
+}
+
+/// A `[[disc]]` table. Its tracks are listed in order in `AlbumTags::tracks`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscTags {
+    pub subtitle: Option<String>,
+    /// Replaces the album's picture for this disc's tracks.
+    pub picture_path: Option<String>,
+    pub track_count: usize,
+}
+
+/// A `[[side]]` table of a vinyl release. Its tracks are listed in order in `AlbumTags::tracks`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SideTags {
+    /// Prefix of the side's track positions, "A", "B", ... unless set.
+    pub name: String,
+    pub track_count: usize,
+}
+
+/// How tracks are numbered on multi-disc albums.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackNumbering {
+    /// 1..N across the whole album, the track total is the album's length.
+    #[default]
+    Album,
+    /// Restart at 1 on each disc, the track total is the disc's length.
+    PerDisc,
+}
 
 #[derive(Debug, Clone)]
 pub struct AlbumTags {
     pub album_name: Option<String>,
     pub artist_name: Option<String>,
+    /// Defaults to `artist_name`, so per-track artists don't split the album.
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    pub compilation: Option<bool>,
     pub year: Option<i64>,
     pub genre: Vec<String>,
     pub picture_path: Option<String>,
     pub tracks: Vec<String>,
+    /// One entry per track when the config uses `[[track]]` tables, otherwise empty.
+    pub track_overrides: Vec<TrackOverrides>,
+    /// One entry per disc when the config uses `[[disc]]` tables, otherwise empty.
+    pub discs: Vec<DiscTags>,
+    /// One entry per side when the config uses `[[side]]` tables, otherwise empty.
+    /// `discs` then holds the records the sides are grouped into.
+    pub sides: Vec<SideTags>,
+    /// Field the side positions are written to, `DEFAULT_POSITION_TAG` when `None`.
+    pub position_tag: Option<String>,
     pub disc_total: Option<i64>,
     pub tracks_per_disc: Option<Vec<i64>>,
+    pub track_numbering: TrackNumbering,
 }
 
 impl fmt::Display for AlbumTags {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "album_name: {:?}\n", self.album_name)
+        writeln!(f, "album_name: {:?}", self.album_name)
+        .and_then(|_|
+            writeln!(f, "artist_name: {:?}", self.artist_name)
+        )
+        .and_then(|_|
+            writeln!(f, "album_artist: {:?}", self.album_artist)
+        )
+        .and_then(|_|
+            writeln!(f, "composer: {:?}", self.composer)
+        )
+        .and_then(|_|
+            writeln!(f, "compilation: {:?}", self.compilation)
+        )
         .and_then(|_|
-            write!(f, "artist_name: {:?}\n", self.artist_name)
+            writeln!(f, "year: {:?}", self.year)
         )
         .and_then(|_|
-            write!(f, "year: {:?}\n", self.year)
+            writeln!(f, "genre: {:?}", self.genre)
         )
         .and_then(|_|
-            write!(f, "genre: {:?}\n", self.genre)
+            writeln!(f, "tracks: {:?}", self.tracks)
         )
         .and_then(|_|
-            write!(f, "tracks: {:?}\n", self.tracks)
+            writeln!(f, "disc_total: {:?}", self.disc_total)
         )
         .and_then(|_|
-            write!(f, "disc_total: {:?}\n", self.disc_total)
+            writeln!(f, "discs: {:?}", self.discs)
+        )
+        .and_then(|_|
+            writeln!(f, "sides: {:?}", self.sides)
+        )
+        .and_then(|_|
+            writeln!(f, "position_tag: {:?}", self.position_tag)
+        )
+        .and_then(|_|
+            writeln!(f, "track_numbering: {:?}", self.track_numbering)
         )
         .and_then(|_|
             write!(f, "tracks_per_disc: {:?}", self.tracks_per_disc)
@@ -50,53 +125,236 @@ impl fmt::Display for AlbumTags {
 
 impl AlbumTags {
     pub fn from_toml(table: Table) -> Result<Self, ConfigError> {
-        if !table.contains_key("tracks") {
-            return Err(ConfigError::MissingKey(String::from("tracks")))
-        }
-
-        let year = match get_i64_value(&table, &["year"]) {
-            Ok(x) => Some(x),
-            _ => None
-        };
+        validate_album_table(&table)?;
+        let config: AlbumConfig = Value::Table(table).try_into()?;
+        AlbumTags::from_config(config)
+    }
 
-        let tracks = match get_string_array(&table, &["tracks"]) {
-            Ok(arr) => arr,
-            Err(e) => return Err(e)
-        };
+    pub fn from_config(config: AlbumConfig) -> Result<Self, ConfigError> {
+        let AlbumConfig {
+            schema: _,
+            // resolved by `config_chain` when the config is loaded from a file
+            extends: _,
+            include: _,
+            version,
+            album,
+            artist,
+            album_artist,
+            composer,
+            compilation,
+            year,
+            genre,
+            picture,
+            tracks,
+            track,
+            disc,
+            side,
+            sides_per_disc,
+            position_tag,
+            disc_total,
+            tracks_per_disc,
+            track_numbering,
+            // read by `SettingsConfig::from_album_config`, they aren't tags
+            settings: _,
+        } = config;
 
-        let disc_total = match get_i64_value(&table, &["disc_total"]) {
-            Ok(x) => Some(x),
-            _ => None
-        };
-        let tracks_per_disc = match get_i64_array(&table, &["tracks_per_disc"]) {
-            Ok(arr) => Some(arr),
-            _ => None
-        };
+        if let Some(version) = version.filter(|v| *v != CONFIG_VERSION) {
+            return Err(ConfigError::TypeError {
+                key: String::from("version"),
+                message: format!("version {} isn't supported, this riptagger reads version {}", version, CONFIG_VERSION),
+            });
+        }
 
-        let mut pic_path_str: Option<String> = None;
-        // picture path should be relative to the config file
-        if let Some(relative_picture_path) = table.get("picture").and_then(|p| Some(p.as_str())).and_then(|p| p.to_owned()) {
-            let cwd = get_current_directory();
-            if let Ok(pic_path_root) = cwd {
-                pic_path_str = pic_path_root.as_path().join(Path::new(&relative_picture_path)).to_str().and_then(|s| Some(s.to_string()));
+        let flat_layout = [
+            ("tracks", tracks.is_some()),
+            ("track", track.is_some()),
+            ("disc_total", disc_total.is_some()),
+            ("tracks_per_disc", tracks_per_disc.is_some()),
+        ];
+        let ((tracks, track_overrides), discs, sides) = match (disc, side) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::TypeError {
+                    key: String::from("side"),
+                    message: String::from("use either [[disc]] or [[side]] tables, not both"),
+                })
             }
-        }
+            (Some(disc_tables), None) => {
+                check_no_flat_layout(&flat_layout, "[[disc]]")?;
+                let (tracks, discs) = parse_disc_tables(disc_tables)?;
+                (tracks, discs, Vec::new())
+            }
+            (None, Some(side_tables)) => {
+                check_no_flat_layout(&flat_layout, "[[side]]")?;
+                let sides_per_disc = match sides_per_disc {
+                    Some(n) if n < 1 => {
+                        return Err(ConfigError::TypeError {
+                            key: String::from("sides_per_disc"),
+                            message: String::from("sides_per_disc should be at least 1"),
+                        })
+                    }
+                    Some(n) => n as usize,
+                    // a record has two sides
+                    None => 2,
+                };
+                parse_side_tables(side_tables, sides_per_disc)?
+            }
+            (None, None) => (parse_tracks(tracks, track, "")?, Vec::new(), Vec::new()),
+        };
 
-        // FIXME: this is not very safe
         Ok(AlbumTags {
-            artist_name: get_string_value(&table, &["artist"]).ok(),
-            album_name: get_string_value(&table, &["album"]).ok(),
+            album_artist: album_artist.or(artist.clone()),
+            artist_name: artist,
+            compilation,
+            composer,
+            album_name: album,
             year,
-            genre: get_single_or_array_string(&table, &["genre"]).unwrap_or(vec![]),
-            picture_path: pic_path_str,
-            tracks: tracks,
-            disc_total: disc_total,
-            tracks_per_disc: tracks_per_disc,
+            genre: genre.map(Vec::from).unwrap_or_default(),
+            picture_path: picture_path(picture),
+            tracks,
+            track_overrides,
+            discs,
+            sides,
+            position_tag,
+            disc_total,
+            tracks_per_disc,
+            track_numbering: track_numbering.unwrap_or_default(),
         })
     }
 }
 
-fn get_disc_number(tracks_per_disc: &Vec<i64>, track_num: i64) -> i64 {
+/// Each disc or side lists its own tracks, so the flat layout can't be mixed in.
+/// `flat_layout` pairs each flat layout key with whether the config sets it.
+fn check_no_flat_layout(flat_layout: &[(&str, bool)], layout: &str) -> Result<(), ConfigError> {
+    match flat_layout.iter().find(|(_, set)| *set) {
+        Some((key, _)) => Err(ConfigError::TypeError {
+            key: key.to_string(),
+            message: format!("'{}' can't be used together with {} tables", key, layout),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Track titles, with their overrides when given as `[[track]]` tables.
+type Tracks = (Vec<String>, Vec<TrackOverrides>);
+
+/// A picture path as written, with `~` expanded. Relative paths are relative to
+/// the current directory, `config_chain` makes them relative to the config file.
+fn picture_path(picture: Option<String>) -> Option<String> {
+    picture.map(|p| expand_home(&p).to_string_lossy().to_string())
+}
+
+/// Track titles from either a `tracks` array or `[[track]]` tables.
+/// `prefix` locates the table they're in, for error messages.
+fn parse_tracks(tracks: Option<Vec<String>>, track_tables: Option<Vec<TrackConfig>>, prefix: &str) -> Result<Tracks, ConfigError> {
+    match (tracks, track_tables) {
+        (Some(_), Some(_)) => Err(ConfigError::TypeError {
+            key: format!("{}track", prefix),
+            message: format!("use either '{}tracks' or [[{}track]] tables, not both", prefix, prefix),
+        }),
+        (Some(tracks), None) => Ok((tracks, Vec::new())),
+        (None, Some(track_tables)) => parse_track_tables(track_tables, prefix),
+        (None, None) => Err(ConfigError::MissingKey(format!("{}tracks", prefix))),
+    }
+}
+
+/// Titles and overrides from `[[track]]` tables.
+fn parse_track_tables(track_tables: Vec<TrackConfig>, prefix: &str) -> Result<Tracks, ConfigError> {
+    let mut titles = Vec::new();
+    let mut overrides = Vec::new();
+    for (i, track) in track_tables.into_iter().enumerate() {
+        titles.push(
+            track
+                .title
+                .ok_or_else(|| ConfigError::MissingKey(format!("{}track[{}].title", prefix, i)))?,
+        );
+        overrides.push(TrackOverrides {
+            artist_name: track.artist,
+            composer: track.composer,
+            year: track.year,
+            genre: track.genre.map(Vec::from),
+        });
+    }
+    Ok((titles, overrides))
+}
+
+/// Tracks, overrides and discs from `[[disc]]` tables, in disc order.
+fn parse_disc_tables(disc_tables: Vec<DiscConfig>) -> Result<(Tracks, Vec<DiscTags>), ConfigError> {
+    let mut titles = Vec::new();
+    let mut overrides = Vec::new();
+    let mut discs = Vec::new();
+    for (i, disc) in disc_tables.into_iter().enumerate() {
+        let prefix = format!("disc[{}].", i);
+        let (disc_titles, mut disc_overrides) = parse_tracks(disc.tracks, disc.track, &prefix)?;
+        if disc_titles.is_empty() {
+            return Err(ConfigError::TypeError {
+                key: format!("disc[{}]", i),
+                message: format!("disc[{}] has no tracks", i),
+            });
+        }
+        // a `tracks` array has no overrides, keep them lined up with the titles
+        disc_overrides.resize(disc_titles.len(), TrackOverrides::default());
+
+        discs.push(DiscTags {
+            subtitle: disc.subtitle,
+            picture_path: picture_path(disc.picture),
+            track_count: disc_titles.len(),
+        });
+        titles.extend(disc_titles);
+        overrides.extend(disc_overrides);
+    }
+    Ok(((titles, overrides), discs))
+}
+
+/// The name of side `i` when the config doesn't name it: A to Z, then AA, AB, ...
+fn side_name(i: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = i + 1;
+    while n > 0 {
+        n -= 1;
+        name.push(char::from(b'A' + (n % 26) as u8));
+        n /= 26;
+    }
+    name.iter().rev().collect()
+}
+
+/// Tracks, overrides and sides from `[[side]]` tables, with the sides grouped
+/// `sides_per_disc` to a disc.
+fn parse_side_tables(side_tables: Vec<SideConfig>, sides_per_disc: usize) -> Result<(Tracks, Vec<DiscTags>, Vec<SideTags>), ConfigError> {
+    let mut titles = Vec::new();
+    let mut overrides = Vec::new();
+    let mut sides = Vec::new();
+    for (i, side) in side_tables.into_iter().enumerate() {
+        let prefix = format!("side[{}].", i);
+        let (side_titles, mut side_overrides) = parse_tracks(side.tracks, side.track, &prefix)?;
+        if side_titles.is_empty() {
+            return Err(ConfigError::TypeError {
+                key: format!("side[{}]", i),
+                message: format!("side[{}] has no tracks", i),
+            });
+        }
+        side_overrides.resize(side_titles.len(), TrackOverrides::default());
+
+        sides.push(SideTags {
+            name: side
+                .name
+                .unwrap_or_else(|| side_name(i)),
+            track_count: side_titles.len(),
+        });
+        titles.extend(side_titles);
+        overrides.extend(side_overrides);
+    }
+
+    let discs = sides
+        .chunks(sides_per_disc)
+        .map(|record| DiscTags {
+            track_count: record.iter().map(|side| side.track_count).sum(),
+            ..DiscTags::default()
+        })
+        .collect();
+    Ok(((titles, overrides), discs, sides))
+}
+
+fn get_disc_number(tracks_per_disc: &[i64], track_num: i64) -> i64 {
     let mut x = 0;
     let mut i: usize = 0;
     while x <= track_num && i < tracks_per_disc.len() {
@@ -106,26 +364,82 @@ fn get_disc_number(tracks_per_disc: &Vec<i64>, track_num: i64) -> i64 {
     i as i64
 }
 
+/// The number and track total of each of `track_total` tracks, restarting on
+/// each disc. Tracks past the last disc are numbered as one more disc.
+fn per_disc_numbers(disc_sizes: &[usize], track_total: usize) -> Vec<(i64, i64)> {
+    let mut numbers = Vec::new();
+    for size in disc_sizes {
+        numbers.extend((1..=*size).map(|n| (n as i64, *size as i64)));
+    }
+    let rest = track_total.saturating_sub(numbers.len());
+    numbers.extend((1..=rest).map(|n| (n as i64, rest as i64)));
+    numbers.truncate(track_total);
+    numbers
+}
+
 pub fn to_track_tags(album: AlbumTags) -> Vec<TrackTags> {
+    // with [[disc]] tables, each track's disc and the disc total follow from the tables
+    let track_discs: Vec<(i64, &DiscTags)> = album
+        .discs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, disc)| std::iter::repeat_n((i as i64 + 1, disc), disc.track_count))
+        .collect();
+    // "A1", "A2", "B1", ... with [[side]] tables
+    let positions: Vec<String> = album
+        .sides
+        .iter()
+        .flat_map(|side| (1..=side.track_count).map(|n| format!("{}{}", side.name, n)))
+        .collect();
+    let disc_total = match album.discs.len() {
+        0 => album.disc_total,
+        n => Some(n as i64),
+    };
+
+    let track_total = album.tracks.len();
+    let numbers: Vec<(i64, i64)> = match album.track_numbering {
+        TrackNumbering::Album => (1..=track_total).map(|n| (n as i64, track_total as i64)).collect(),
+        TrackNumbering::PerDisc => {
+            let disc_sizes: Vec<usize> = if !album.discs.is_empty() {
+                album.discs.iter().map(|d| d.track_count).collect()
+            } else if let Some(tpd) = &album.tracks_per_disc {
+                tpd.iter().map(|n| (*n).max(0) as usize).collect()
+            } else {
+                vec![track_total]
+            };
+            per_disc_numbers(&disc_sizes, track_total)
+        }
+    };
+
     let mut tags = Vec::new();
     let mut index = 0;
-    let track_total = album.tracks.len();
     while index < track_total {
-        let disc_num = match &album.tracks_per_disc {
-            Some(tpd) => Some(get_disc_number(tpd, index as i64)),
-            None => None
+        let disc = track_discs.get(index);
+        let disc_num = match disc {
+            Some((number, _)) => Some(*number),
+            None => album.tracks_per_disc.as_ref().map(|tpd| get_disc_number(tpd, index as i64)),
         };
+        let picture_path = disc
+            .and_then(|(_, d)| d.picture_path.clone())
+            .or(album.picture_path.clone());
+        let overrides = album.track_overrides.get(index).cloned().unwrap_or_default();
         tags.push(TrackTags {
             album_name: album.album_name.clone(),
-            artist_name: album.artist_name.clone(),
-            year: album.year.clone(),
+            artist_name: overrides.artist_name.or(album.artist_name.clone()),
+            album_artist: album.album_artist.clone(),
+            composer: overrides.composer.or(album.composer.clone()),
+            year: overrides.year.or(album.year),
             track_name: album.tracks[index].clone(),
-            genre: album.genre.clone(),
-            picture_path: album.picture_path.clone(),
-            track_number: Some((index + 1) as i64),
-            track_total: Some(track_total as i64),
+            genre: overrides.genre.unwrap_or(album.genre.clone()),
+            picture_path,
+            track_number: Some(numbers[index].0),
+            track_total: Some(numbers[index].1),
             disc_number: disc_num,
-            disc_total: album.disc_total,
+            disc_total,
+            disc_subtitle: disc.and_then(|(_, d)| d.subtitle.clone()),
+            position: positions.get(index).cloned(),
+            position_tag: album.position_tag.clone(),
+            compilation: album.compilation,
         });
 
         index += 1;
@@ -0,0 +1,153 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use ape::{Item, ItemType, Tag};
+
+use crate::tag_writer::{format_number_of, parse_flag, parse_number_of, parse_year, CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{TagError, TrackTags, DEFAULT_POSITION_TAG};
+
+pub const FRONT_COVER_KEY: &str = "Cover Art (Front)";
+
+/// Read the APEv2 tag of a file, starting from an empty tag if it has none.
+pub fn read_ape_tag(track_path: &str) -> Result<Tag, TagError> {
+    match ape::read_from_path(track_path) {
+        Ok(tag) => Ok(tag),
+        Err(ape::Error::TagNotFound) => Ok(Tag::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Copy `tags` into the items of `tag`.
+pub fn apply_ape_items(tags: &TrackTags, tag: &mut Tag) -> Result<(), TagError> {
+    let mut text: Vec<(&str, Vec<String>)> = Vec::new();
+
+    if let Some(album_name) = &tags.album_name {
+        text.push(("Album", vec![album_name.clone()]));
+    }
+
+    if let Some(artist_name) = &tags.artist_name {
+        text.push(("Artist", vec![artist_name.clone()]));
+    }
+
+    if let Some(album_artist) = &tags.album_artist {
+        text.push(("Album Artist", vec![album_artist.clone()]));
+    }
+
+    if let Some(composer) = &tags.composer {
+        text.push(("Composer", vec![composer.clone()]));
+    }
+
+    text.push(("Title", vec![tags.track_name.clone()]));
+
+    if let Some(track) = format_number_of(tags.track_number, tags.track_total) {
+        text.push(("Track", vec![track]));
+    }
+
+    if let Some(disc) = format_number_of(tags.disc_number, tags.disc_total) {
+        text.push(("Disc", vec![disc]));
+    }
+
+    if let Some(disc_subtitle) = &tags.disc_subtitle {
+        text.push(("DiscSubtitle", vec![disc_subtitle.clone()]));
+    }
+
+    if let Some(position) = &tags.position {
+        text.push((tags.position_key(), vec![position.clone()]));
+    }
+
+    if let Some(year) = tags.year {
+        text.push(("Year", vec![year.to_string()]));
+    }
+
+    if !tags.genre.is_empty() {
+        text.push(("Genre", tags.genre.clone()));
+    }
+
+    for (key, values) in text {
+        // multiple values share one item, separated by a null byte
+        tag.set_item(Item::new(key, ItemType::Text, values.join("\0"))?);
+    }
+
+    match tags.compilation {
+        Some(true) => tag.set_item(Item::new("Compilation", ItemType::Text, "1")?),
+        Some(false) => {
+            tag.remove_items("Compilation");
+        }
+        None => (),
+    }
+
+    if let Some(cover) = CoverPicture::from_tags(tags)? {
+        // binary cover items start with a null-terminated file name
+        let mut value = format!("cover.{}\0", cover.extension()).into_bytes();
+        value.extend(cover.data);
+        tag.set_item(Item::new(FRONT_COVER_KEY, ItemType::Binary, value)?);
+    }
+
+    Ok(())
+}
+
+/// Map the text items of `tag` back onto `TrackTags`.
+pub fn track_tags_from_ape(tag: &Tag, position_tag: Option<&str>) -> TrackTags {
+    let values = |key: &str| -> Vec<String> {
+        tag.item(key)
+            .and_then(|item| <Vec<&str>>::try_from(item).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    };
+    let first = |key: &str| values(key).into_iter().next();
+    let (track_number, track_total) = first("Track").map(|v| parse_number_of(&v)).unwrap_or_default();
+    let (disc_number, disc_total) = first("Disc").map(|v| parse_number_of(&v)).unwrap_or_default();
+
+    TrackTags {
+        album_name: first("Album"),
+        artist_name: first("Artist"),
+        album_artist: first("Album Artist"),
+        composer: first("Composer"),
+        year: first("Year").and_then(|v| parse_year(&v)),
+        track_name: first("Title").unwrap_or_default(),
+        genre: values("Genre"),
+        picture_path: None,
+        track_number,
+        track_total,
+        disc_number,
+        disc_total,
+        disc_subtitle: first("DiscSubtitle"),
+        position: first(position_tag.unwrap_or(DEFAULT_POSITION_TAG)),
+        position_tag: position_tag.map(String::from),
+        compilation: first("Compilation").map(|v| parse_flag(&v)),
+    }
+}
+
+/// An APEv2 tag at the end of a WavPack, Monkey's Audio or Musepack file.
+pub struct ApeWriter;
+
+struct ApeFile {
+    path: String,
+    tag: Tag,
+}
+
+impl TagWriter for ApeWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        Ok(Box::new(ApeFile {
+            path: track_path.to_string(),
+            tag: read_ape_tag(track_path)?,
+        }))
+    }
+}
+
+impl TrackFile for ApeFile {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_ape(&self.tag, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        apply_ape_items(tags, &mut self.tag)
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        ape::write_to_path(&self.tag, &self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::fs::{read, write};
+
+use crate::tag_writer::{parse_flag, parse_number_of, parse_year, CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{picture_mime_type, TagError, TrackTags, DEFAULT_POSITION_TAG};
+
+/// Build a GUID in the mixed-endian byte order ASF stores on disk.
+const fn guid(d1: u32, d2: u16, d3: u16, d4: [u8; 8]) -> [u8; 16] {
+    let a = d1.to_le_bytes();
+    let b = d2.to_le_bytes();
+    let c = d3.to_le_bytes();
+    [
+        a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1],
+        d4[0], d4[1], d4[2], d4[3], d4[4], d4[5], d4[6], d4[7],
+    ]
+}
+
+const HEADER_GUID: [u8; 16] = guid(0x75B22630, 0x668E, 0x11CF, [0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C]);
+const FILE_PROPERTIES_GUID: [u8; 16] = guid(0x8CABDCA1, 0xA947, 0x11CF, [0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65]);
+const CONTENT_DESCRIPTION_GUID: [u8; 16] = guid(0x75B22633, 0x668E, 0x11CF, [0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C]);
+const EXTENDED_CONTENT_DESCRIPTION_GUID: [u8; 16] = guid(0xD2D0A440, 0xE307, 0x11D2, [0x97, 0xF0, 0x00, 0xA0, 0xC9, 0x5E, 0xA8, 0x50]);
+const HEADER_EXTENSION_GUID: [u8; 16] = guid(0x5FBF03B5, 0xA92E, 0x11CF, [0x8E, 0xE3, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65]);
+const HEADER_EXTENSION_RESERVED_GUID: [u8; 16] = guid(0xABD3D211, 0xA9BA, 0x11CF, [0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65]);
+const METADATA_LIBRARY_GUID: [u8; 16] = guid(0x44231C94, 0x9498, 0x49D1, [0xA1, 0x41, 0x1D, 0x13, 0x4E, 0x45, 0x70, 0x54]);
+
+/// Size of the GUID + size prefix every ASF object starts with.
+const OBJECT_HEADER_SIZE: usize = 24;
+/// Offset of the file size field within the File Properties Object data.
+const FILE_SIZE_OFFSET: usize = 16;
+
+const PICTURE_NAME: &str = "WM/Picture";
+const FRONT_COVER: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AsfValueType {
+    Unicode = 0,
+    Bytes = 1,
+    Bool = 2,
+    DWord = 3,
+    QWord = 4,
+    Word = 5,
+    Guid = 6,
+}
+
+impl AsfValueType {
+    fn from_u16(v: u16) -> Result<Self, TagError> {
+        use AsfValueType::*;
+        match v {
+            0 => Ok(Unicode),
+            1 => Ok(Bytes),
+            2 => Ok(Bool),
+            3 => Ok(DWord),
+            4 => Ok(QWord),
+            5 => Ok(Word),
+            6 => Ok(Guid),
+            _ => Err(invalid("unknown attribute type")),
+        }
+    }
+}
+
+/// A name/value attribute from the Extended Content Description
+/// or Metadata Library objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsfAttribute {
+    pub name: String,
+    pub value_type: AsfValueType,
+    pub value: Vec<u8>,
+}
+
+impl AsfAttribute {
+    fn unicode(name: &str, value: &str) -> Self {
+        AsfAttribute {
+            name: name.to_string(),
+            value_type: AsfValueType::Unicode,
+            value: encode_utf16(value),
+        }
+    }
+
+    /// Booleans take 4 bytes in the Extended Content Description.
+    fn bool(name: &str, value: bool) -> Self {
+        AsfAttribute {
+            name: name.to_string(),
+            value_type: AsfValueType::Bool,
+            value: (value as u32).to_le_bytes().to_vec(),
+        }
+    }
+
+    fn dword(name: &str, value: u32) -> Self {
+        AsfAttribute {
+            name: name.to_string(),
+            value_type: AsfValueType::DWord,
+            value: value.to_le_bytes().to_vec(),
+        }
+    }
+
+    /// The value as text, for string, boolean and integer attributes.
+    pub fn as_string(&self) -> Option<String> {
+        match self.value_type {
+            AsfValueType::Unicode => Some(decode_utf16(&self.value)),
+            AsfValueType::DWord => self
+                .value
+                .get(0..4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()).to_string()),
+            // 4 bytes in the Extended Content Description, 2 in the Metadata Library
+            AsfValueType::Bool => Some(String::from(match self.value.iter().any(|b| *b != 0) {
+                true => "1",
+                false => "0",
+            })),
+            _ => None,
+        }
+    }
+
+    fn is_front_cover(&self) -> bool {
+        self.name == PICTURE_NAME && self.value.first() == Some(&FRONT_COVER)
+    }
+}
+
+/// The tag-related contents of an ASF header.
+#[derive(Debug, Clone, Default)]
+pub struct AsfTags {
+    /// Title, author, copyright, description and rating from the Content Description Object.
+    pub content_description: [String; 5],
+    pub extended: Vec<AsfAttribute>,
+    /// File-wide attributes from the Metadata Library Object, used for values too large for `extended`.
+    pub library: Vec<AsfAttribute>,
+}
+
+impl AsfTags {
+    pub fn title(&self) -> &str {
+        &self.content_description[0]
+    }
+
+    pub fn author(&self) -> &str {
+        &self.content_description[1]
+    }
+
+    /// All attributes called `name`, from both attribute objects.
+    pub fn get(&self, name: &str) -> Vec<&AsfAttribute> {
+        self.extended
+            .iter()
+            .chain(self.library.iter())
+            .filter(|a| a.name == name)
+            .collect()
+    }
+
+    fn set(&mut self, attributes: Vec<AsfAttribute>) {
+        if let Some(name) = attributes.first().map(|a| a.name.clone()) {
+            self.extended.retain(|a| a.name != name);
+            self.library.retain(|a| a.name != name);
+            self.extended.extend(attributes);
+        }
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.extended.retain(|a| a.name != name);
+        self.library.retain(|a| a.name != name);
+    }
+
+    fn set_front_cover(&mut self, data: Vec<u8>) {
+        self.extended.retain(|a| !a.is_front_cover());
+        self.library.retain(|a| !a.is_front_cover());
+
+        let mut value = vec![FRONT_COVER];
+        value.extend((data.len() as u32).to_le_bytes());
+        value.extend(encode_utf16(picture_mime_type(&data)));
+        value.extend(encode_utf16(""));
+        value.extend(data);
+
+        let picture = AsfAttribute {
+            name: PICTURE_NAME.to_string(),
+            value_type: AsfValueType::Bytes,
+            value,
+        };
+        // Extended Content Description values have a 16-bit length
+        if picture.value.len() <= u16::MAX as usize {
+            self.extended.push(picture);
+        } else {
+            self.library.push(picture);
+        }
+    }
+}
+
+fn invalid(msg: &str) -> TagError {
+    TagError::InvalidFile(format!("ASF: {}", msg))
+}
+
+/// Convert a length for a fixed-width field, naming what it measures if it doesn't fit.
+fn length<T: TryFrom<usize>>(len: usize, what: &str) -> Result<T, TagError> {
+    T::try_from(len).map_err(|_| TagError::TooLarge(format!("ASF: {} is {} bytes, too long for its length field", what, len)))
+}
+
+/// UTF-16LE with a null terminator, as ASF stores strings.
+fn encode_utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|c| c.to_le_bytes())
+        .collect()
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Bounds-checked little-endian reader over an object's data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], TagError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid("truncated object"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, TagError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, TagError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, TagError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn guid(&mut self) -> Result<[u8; 16], TagError> {
+        Ok(self.bytes(16)?.try_into().unwrap())
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AsfObject {
+    guid: [u8; 16],
+    data: Vec<u8>,
+}
+
+impl AsfObject {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.guid.to_vec();
+        bytes.extend(((self.data.len() + OBJECT_HEADER_SIZE) as u64).to_le_bytes());
+        bytes.extend(&self.data);
+        bytes
+    }
+}
+
+fn read_objects(data: &[u8]) -> Result<Vec<AsfObject>, TagError> {
+    let mut reader = Reader::new(data);
+    let mut objects = Vec::new();
+    while reader.remaining() >= OBJECT_HEADER_SIZE {
+        let guid = reader.guid()?;
+        let size = reader.u64()? as usize;
+        if size < OBJECT_HEADER_SIZE {
+            return Err(invalid("object size too small"));
+        }
+        let data = reader.bytes(size - OBJECT_HEADER_SIZE)?.to_vec();
+        objects.push(AsfObject { guid, data });
+    }
+    Ok(objects)
+}
+
+/// The top-level Header Object and the offset where the Data Object begins.
+struct AsfHeader {
+    reserved: [u8; 2],
+    objects: Vec<AsfObject>,
+    end: usize,
+}
+
+impl AsfHeader {
+    fn parse(file: &[u8]) -> Result<Self, TagError> {
+        let mut reader = Reader::new(file);
+        if reader.guid()? != HEADER_GUID {
+            return Err(invalid("missing header object"));
+        }
+        let end = reader.u64()? as usize;
+        let _count = reader.u32()?;
+        let reserved = reader.bytes(2)?.try_into().unwrap();
+        let objects_start = reader.pos;
+        let objects = read_objects(
+            file.get(objects_start..end)
+                .ok_or_else(|| invalid("truncated header object"))?,
+        )?;
+        Ok(AsfHeader {
+            reserved,
+            objects,
+            end,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let children: Vec<u8> = self.objects.iter().flat_map(|o| o.to_bytes()).collect();
+        let mut bytes = HEADER_GUID.to_vec();
+        bytes.extend(((children.len() + OBJECT_HEADER_SIZE + 6) as u64).to_le_bytes());
+        bytes.extend((self.objects.len() as u32).to_le_bytes());
+        bytes.extend(self.reserved);
+        bytes.extend(children);
+        bytes
+    }
+
+    fn find(&self, guid: [u8; 16]) -> Option<&AsfObject> {
+        self.objects.iter().find(|o| o.guid == guid)
+    }
+
+    /// Replace the object with the same GUID, or append it.
+    fn put(&mut self, object: AsfObject) {
+        match self.objects.iter_mut().find(|o| o.guid == object.guid) {
+            Some(existing) => *existing = object,
+            None => self.objects.push(object),
+        }
+    }
+}
+
+fn parse_content_description(data: &[u8]) -> Result<[String; 5], TagError> {
+    let mut reader = Reader::new(data);
+    let mut lengths = [0usize; 5];
+    for len in lengths.iter_mut() {
+        *len = reader.u16()? as usize;
+    }
+    let mut fields: [String; 5] = Default::default();
+    for (field, len) in fields.iter_mut().zip(lengths) {
+        *field = decode_utf16(reader.bytes(len)?);
+    }
+    Ok(fields)
+}
+
+fn content_description_bytes(fields: &[String; 5]) -> Result<Vec<u8>, TagError> {
+    let encoded: Vec<Vec<u8>> = fields.iter().map(|f| encode_utf16(f)).collect();
+    let mut data = Vec::new();
+    for e in &encoded {
+        data.extend(length::<u16>(e.len(), "a content description field")?.to_le_bytes());
+    }
+    for e in encoded {
+        data.extend(e);
+    }
+    Ok(data)
+}
+
+fn parse_extended_content_description(data: &[u8]) -> Result<Vec<AsfAttribute>, TagError> {
+    let mut reader = Reader::new(data);
+    let count = reader.u16()?;
+    let mut attributes = Vec::new();
+    for _ in 0..count {
+        let name_len = reader.u16()? as usize;
+        let name = decode_utf16(reader.bytes(name_len)?);
+        let value_type = AsfValueType::from_u16(reader.u16()?)?;
+        let value_len = reader.u16()? as usize;
+        let value = reader.bytes(value_len)?.to_vec();
+        attributes.push(AsfAttribute { name, value_type, value });
+    }
+    Ok(attributes)
+}
+
+fn extended_content_description_bytes(attributes: &[AsfAttribute]) -> Result<Vec<u8>, TagError> {
+    let mut data = length::<u16>(attributes.len(), "the attribute count")?.to_le_bytes().to_vec();
+    for a in attributes {
+        let name = encode_utf16(&a.name);
+        data.extend(length::<u16>(name.len(), &a.name)?.to_le_bytes());
+        data.extend(name);
+        data.extend((a.value_type as u16).to_le_bytes());
+        data.extend(length::<u16>(a.value.len(), &a.name)?.to_le_bytes());
+        data.extend(&a.value);
+    }
+    Ok(data)
+}
+
+/// A Metadata Library record, keeping the fields riptagger doesn't set.
+struct LibraryRecord {
+    language_index: u16,
+    stream_number: u16,
+    attribute: AsfAttribute,
+}
+
+fn parse_metadata_library(data: &[u8]) -> Result<Vec<LibraryRecord>, TagError> {
+    let mut reader = Reader::new(data);
+    let count = reader.u16()?;
+    let mut records = Vec::new();
+    for _ in 0..count {
+        let language_index = reader.u16()?;
+        let stream_number = reader.u16()?;
+        let name_len = reader.u16()? as usize;
+        let value_type = AsfValueType::from_u16(reader.u16()?)?;
+        let value_len = reader.u32()? as usize;
+        let name = decode_utf16(reader.bytes(name_len)?);
+        let value = reader.bytes(value_len)?.to_vec();
+        records.push(LibraryRecord {
+            language_index,
+            stream_number,
+            attribute: AsfAttribute { name, value_type, value },
+        });
+    }
+    Ok(records)
+}
+
+fn metadata_library_bytes(records: &[LibraryRecord]) -> Result<Vec<u8>, TagError> {
+    let mut data = length::<u16>(records.len(), "the record count")?.to_le_bytes().to_vec();
+    for r in records {
+        let name = encode_utf16(&r.attribute.name);
+        data.extend(r.language_index.to_le_bytes());
+        data.extend(r.stream_number.to_le_bytes());
+        data.extend(length::<u16>(name.len(), &r.attribute.name)?.to_le_bytes());
+        data.extend((r.attribute.value_type as u16).to_le_bytes());
+        data.extend(length::<u32>(r.attribute.value.len(), &r.attribute.name)?.to_le_bytes());
+        data.extend(name);
+        data.extend(&r.attribute.value);
+    }
+    Ok(data)
+}
+
+/// The Header Extension Object: a reserved GUID and field, then nested objects.
+fn parse_header_extension(data: &[u8]) -> Result<Vec<AsfObject>, TagError> {
+    let mut reader = Reader::new(data);
+    reader.bytes(18)?;
+    let size = reader.u32()? as usize;
+    read_objects(reader.bytes(size)?)
+}
+
+fn header_extension_bytes(objects: &[AsfObject]) -> Vec<u8> {
+    let children: Vec<u8> = objects.iter().flat_map(|o| o.to_bytes()).collect();
+    let mut data = HEADER_EXTENSION_RESERVED_GUID.to_vec();
+    data.extend(6u16.to_le_bytes());
+    data.extend((children.len() as u32).to_le_bytes());
+    data.extend(children);
+    data
+}
+
+fn read_tags_from_header(header: &AsfHeader) -> Result<AsfTags, TagError> {
+    let mut tags = AsfTags::default();
+    if let Some(cd) = header.find(CONTENT_DESCRIPTION_GUID) {
+        tags.content_description = parse_content_description(&cd.data)?;
+    }
+    if let Some(ecd) = header.find(EXTENDED_CONTENT_DESCRIPTION_GUID) {
+        tags.extended = parse_extended_content_description(&ecd.data)?;
+    }
+    if let Some(ext) = header.find(HEADER_EXTENSION_GUID) {
+        for object in parse_header_extension(&ext.data)? {
+            if object.guid == METADATA_LIBRARY_GUID {
+                // per-stream records aren't tags, `write_tags_to_header` keeps them as they are
+                tags.library = parse_metadata_library(&object.data)?
+                    .into_iter()
+                    .filter(|r| r.stream_number == 0)
+                    .map(|r| r.attribute)
+                    .collect();
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// Write `tags` back into the header, keeping per-stream Metadata Library
+/// records as they were.
+fn write_tags_to_header(header: &mut AsfHeader, tags: &AsfTags) -> Result<(), TagError> {
+    header.put(AsfObject {
+        guid: CONTENT_DESCRIPTION_GUID,
+        data: content_description_bytes(&tags.content_description)?,
+    });
+    header.put(AsfObject {
+        guid: EXTENDED_CONTENT_DESCRIPTION_GUID,
+        data: extended_content_description_bytes(&tags.extended)?,
+    });
+
+    let mut extension_objects = match header.find(HEADER_EXTENSION_GUID) {
+        Some(ext) => parse_header_extension(&ext.data)?,
+        None => Vec::new(),
+    };
+    let mut records: Vec<LibraryRecord> = Vec::new();
+    if let Some(library) = extension_objects.iter().find(|o| o.guid == METADATA_LIBRARY_GUID) {
+        records = parse_metadata_library(&library.data)?
+            .into_iter()
+            .filter(|r| r.stream_number != 0)
+            .collect();
+    }
+    records.extend(tags.library.iter().map(|a| LibraryRecord {
+        language_index: 0,
+        stream_number: 0,
+        attribute: a.clone(),
+    }));
+    extension_objects.retain(|o| o.guid != METADATA_LIBRARY_GUID);
+    if !records.is_empty() {
+        extension_objects.push(AsfObject {
+            guid: METADATA_LIBRARY_GUID,
+            data: metadata_library_bytes(&records)?,
+        });
+    }
+    if !extension_objects.is_empty() || header.find(HEADER_EXTENSION_GUID).is_some() {
+        header.put(AsfObject {
+            guid: HEADER_EXTENSION_GUID,
+            data: header_extension_bytes(&extension_objects),
+        });
+    }
+    Ok(())
+}
+
+/// The tags of an ASF file, for the tests to check what was written.
+#[cfg(test)]
+pub fn read_asf_tags(track_path: &str) -> Result<AsfTags, TagError> {
+    let file = read(track_path)?;
+    read_tags_from_header(&AsfHeader::parse(&file)?)
+}
+
+/// Map the Content Description and attributes of `asf` back onto `TrackTags`.
+pub fn track_tags_from_asf(asf: &AsfTags, position_tag: Option<&str>) -> TrackTags {
+    let values = |name: &str| -> Vec<String> {
+        asf.get(name).iter().filter_map(|a| a.as_string()).collect()
+    };
+    let first = |name: &str| values(name).into_iter().next();
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    let (disc_number, disc_total) = first("WM/PartOfSet")
+        .map(|v| parse_number_of(&v))
+        .unwrap_or_default();
+
+    TrackTags {
+        album_name: first("WM/AlbumTitle"),
+        artist_name: non_empty(asf.author()),
+        album_artist: first("WM/AlbumArtist"),
+        composer: first("WM/Composer"),
+        year: first("WM/Year").and_then(|v| parse_year(&v)),
+        track_name: asf.title().to_string(),
+        genre: values("WM/Genre"),
+        picture_path: None,
+        track_number: first("WM/TrackNumber").and_then(|v| v.trim().parse().ok()),
+        track_total: None,
+        disc_number,
+        disc_total,
+        disc_subtitle: first("WM/SetSubTitle"),
+        position: first(position_tag.unwrap_or(DEFAULT_POSITION_TAG)),
+        position_tag: position_tag.map(String::from),
+        compilation: first("WM/IsCompilation").map(|v| parse_flag(&v)),
+    }
+}
+
+/// Content Description and attribute objects in the header of an ASF/WMA file.
+pub struct AsfWriter;
+
+struct AsfFile {
+    path: String,
+    file: Vec<u8>,
+    header: AsfHeader,
+    tags: AsfTags,
+}
+
+impl TagWriter for AsfWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        let file = read(track_path)?;
+        let header = AsfHeader::parse(&file)?;
+        let tags = read_tags_from_header(&header)?;
+        Ok(Box::new(AsfFile {
+            path: track_path.to_string(),
+            file,
+            header,
+            tags,
+        }))
+    }
+}
+
+impl TrackFile for AsfFile {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_asf(&self.tags, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        self.tags.content_description[0] = tags.track_name.clone();
+
+        if let Some(artist_name) = &tags.artist_name {
+            self.tags.content_description[1] = artist_name.clone();
+        }
+
+        if let Some(album_artist) = &tags.album_artist {
+            self.tags.set(vec![AsfAttribute::unicode("WM/AlbumArtist", album_artist)]);
+        }
+
+        match tags.compilation {
+            Some(true) => self.tags.set(vec![AsfAttribute::bool("WM/IsCompilation", true)]),
+            Some(false) => self.tags.remove("WM/IsCompilation"),
+            None => (),
+        }
+
+        if let Some(composer) = &tags.composer {
+            self.tags.set(vec![AsfAttribute::unicode("WM/Composer", composer)]);
+        }
+
+        if let Some(album_name) = &tags.album_name {
+            self.tags.set(vec![AsfAttribute::unicode("WM/AlbumTitle", album_name)]);
+        }
+
+        if let Some(track_number) = tags.track_number {
+            self.tags.set(vec![AsfAttribute::dword("WM/TrackNumber", track_number as u32)]);
+        }
+
+        if let Some(year) = tags.year {
+            self.tags.set(vec![AsfAttribute::unicode("WM/Year", &year.to_string())]);
+        }
+
+        if !tags.genre.is_empty() {
+            self.tags.set(tags.genre.iter().map(|g| AsfAttribute::unicode("WM/Genre", g)).collect());
+        }
+
+        if let Some(disc_num) = tags.disc_number {
+            let part_of_set = match tags.disc_total {
+                Some(disc_total) => format!("{}/{}", disc_num, disc_total),
+                None => disc_num.to_string(),
+            };
+            self.tags.set(vec![AsfAttribute::unicode("WM/PartOfSet", &part_of_set)]);
+        }
+
+        if let Some(disc_subtitle) = &tags.disc_subtitle {
+            self.tags.set(vec![AsfAttribute::unicode("WM/SetSubTitle", disc_subtitle)]);
+        }
+
+        if let Some(position) = &tags.position {
+            self.tags.set(vec![AsfAttribute::unicode(tags.position_key(), position)]);
+        }
+
+        if let Some(cover) = CoverPicture::from_tags(tags)? {
+            self.tags.set_front_cover(cover.data);
+        }
+
+        Ok(())
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        let AsfFile { path, file, mut header, tags } = *self;
+        write_tags_to_header(&mut header, &tags)?;
+        let mut new_header = header.to_bytes();
+
+        // the data and index objects don't move relative to each other,
+        // only the total file size recorded in File Properties changes
+        let new_size = (new_header.len() + file.len() - header.end) as u64;
+        let header_start = OBJECT_HEADER_SIZE + 6;
+        let mut offset = header_start;
+        for object in &header.objects {
+            if object.guid == FILE_PROPERTIES_GUID && object.data.len() >= FILE_SIZE_OFFSET + 8 {
+                let field = offset + OBJECT_HEADER_SIZE + FILE_SIZE_OFFSET;
+                new_header[field..field + 8].copy_from_slice(&new_size.to_le_bytes());
+            }
+            offset += object.data.len() + OBJECT_HEADER_SIZE;
+        }
+
+        new_header.extend(&file[header.end..]);
+        write(path, new_header)?;
+        Ok(())
+    }
+}
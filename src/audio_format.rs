@@ -0,0 +1,180 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Container formats riptagger can tag. Each maps to one tag backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioFormat {
+    Flac,
+    Mp3,
+    /// Ogg Vorbis and Ogg Opus
+    Ogg,
+    Asf,
+    Mp4,
+    Ape,
+    Wav,
+    Aiff,
+}
+
+const ASF_HEADER_GUID: [u8; 16] = [
+    0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+/// Bytes needed to recognise any of the supported formats.
+const SNIFF_LEN: usize = 16;
+
+impl AudioFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        use AudioFormat::*;
+        match extension.to_lowercase().as_str() {
+            "flac" => Some(Flac),
+            "mp3" => Some(Mp3),
+            "ogg" | "oga" | "opus" => Some(Ogg),
+            "asf" | "wma" => Some(Asf),
+            "m4a" | "mp4" => Some(Mp4),
+            "wv" | "ape" | "mpc" => Some(Ape),
+            "wav" => Some(Wav),
+            "aiff" | "aif" => Some(Aiff),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Recognise a format from the first bytes of a file. ID3v2 tags must be
+    /// skipped before calling this, see `detect_audio_format`.
+    pub fn from_magic(header: &[u8]) -> Option<Self> {
+        use AudioFormat::*;
+        if header.starts_with(b"fLaC") {
+            Some(Flac)
+        } else if header.starts_with(b"OggS") {
+            Some(Ogg)
+        } else if header.starts_with(&ASF_HEADER_GUID) {
+            Some(Asf)
+        } else if header.get(4..8) == Some(b"ftyp") {
+            Some(Mp4)
+        } else if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WAVE") {
+            Some(Wav)
+        } else if header.starts_with(b"FORM")
+            && matches!(header.get(8..12), Some(b"AIFF") | Some(b"AIFC"))
+        {
+            Some(Aiff)
+        } else if header.starts_with(b"wvpk")
+            || header.starts_with(b"MAC ")
+            || header.starts_with(b"MPCK")
+            || header.starts_with(b"MP+")
+        {
+            Some(Ape)
+        } else if is_mpeg_audio_sync(header) {
+            Some(Mp3)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use AudioFormat::*;
+        let name = match self {
+            Flac => "FLAC",
+            Mp3 => "MP3",
+            Ogg => "Ogg",
+            Asf => "ASF/WMA",
+            Mp4 => "MP4",
+            Ape => "APEv2-tagged (WavPack/Monkey's Audio/Musepack)",
+            Wav => "WAV",
+            Aiff => "AIFF",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An MPEG audio frame header: 11 sync bits, then a valid version and layer.
+/// Layer bits of 00 are reserved, which also rules out AAC ADTS streams.
+fn is_mpeg_audio_sync(header: &[u8]) -> bool {
+    match header {
+        [0xFF, b1, ..] => b1 & 0xE0 == 0xE0 && b1 & 0x18 != 0x08 && b1 & 0x06 != 0,
+        _ => false,
+    }
+}
+
+/// Size of a leading ID3v2 tag, including its header and footer.
+fn id3v2_len(header: &[u8]) -> Option<u64> {
+    if header.len() < 10 || !header.starts_with(b"ID3") {
+        return None;
+    }
+    // tag size is a 28 bit syncsafe integer
+    let size = header[6..10]
+        .iter()
+        .fold(0u64, |acc, b| (acc << 7) | (*b & 0x7F) as u64);
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
+/// What a file contains, and what its extension claims it contains.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedFormat {
+    /// Format found by sniffing the file contents, if recognised.
+    pub content: Option<AudioFormat>,
+    pub extension: Option<AudioFormat>,
+}
+
+impl DetectedFormat {
+    /// The format to tag the file as: contents win over the extension.
+    pub fn format(&self) -> Option<AudioFormat> {
+        self.content.or(self.extension)
+    }
+
+    /// Both formats are known and they disagree.
+    pub fn is_mismatched(&self) -> bool {
+        matches!((self.content, self.extension), (Some(c), Some(e)) if c != e)
+    }
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Sniff the contents of a file, skipping any ID3v2 tags in front of the audio.
+pub fn sniff_audio_format(path: &Path) -> io::Result<Option<AudioFormat>> {
+    let mut file = File::open(path)?;
+    let mut header = [0u8; SNIFF_LEN];
+    let mut offset = 0;
+    loop {
+        let len = read_up_to(&mut file, &mut header)?;
+        match id3v2_len(&header[..len]) {
+            Some(tag_len) => {
+                offset += tag_len;
+                file.seek(SeekFrom::Start(offset))?;
+            }
+            // an ID3 tag followed by nothing recognisable is still most likely MP3
+            None if offset > 0 => {
+                return Ok(AudioFormat::from_magic(&header[..len]).or(Some(AudioFormat::Mp3)))
+            }
+            None => return Ok(AudioFormat::from_magic(&header[..len])),
+        }
+    }
+}
+
+pub fn detect_audio_format(path: &Path) -> io::Result<DetectedFormat> {
+    Ok(DetectedFormat {
+        content: sniff_audio_format(path)?,
+        extension: AudioFormat::from_path(path),
+    })
+}
@@ -2,22 +2,89 @@
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 
 use std::fmt;
-use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use std::error::Error;
 use toml::Table;
 
 use crate::album_tags::AlbumTags;
+use crate::config_chain::{resolve_config, ResolvedConfig};
+use crate::diagnostic::Diagnostic;
 
 #[derive(Debug)]
 pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Yaml(serde_yaml::Error),
+    Json(serde_json::Error),
+    /// The path of the missing key, such as `track[2].title`.
     MissingKey(String),
-    TypeError(String),
+    /// A value of the wrong type, or one riptagger can't use, at the key path `key`.
+    TypeError { key: String, message: String },
+    /// A key the config doesn't use, with the closest known key if it looks like a typo.
+    UnknownKey {
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// Values that are fine on their own but contradict each other. `key` is the
+    /// path of the value the message is about.
+    Inconsistent { key: String, message: String },
+    /// More than one problem was found.
+    Invalid(Vec<ConfigError>),
+    /// An error in the config file `path`, with diagnostics pointing into the file.
+    /// Shown as the plain error, or as the diagnostics with `{:#}`.
+    InFile {
+        path: String,
+        diagnostics: Vec<Diagnostic>,
+        error: Box<ConfigError>,
+    },
 }
 
-impl From<&str> for ConfigError {
-    fn from(s: &str) -> Self {
-        ConfigError::TypeError(s.to_string())
+impl ConfigError {
+    /// The path of the key the error is about, such as `disc[1].tracks`.
+    pub fn key_path(&self) -> Option<String> {
+        use ConfigError::*;
+        match self {
+            MissingKey(key) | UnknownKey { key, .. } | TypeError { key, .. } | Inconsistent { key, .. } => {
+                Some(key.clone())
+            }
+            InFile { error, .. } => error.key_path(),
+            _ => None,
+        }
+    }
+
+    /// The error without its place in the file.
+    pub fn inner(&self) -> &ConfigError {
+        match self {
+            ConfigError::InFile { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ConfigError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ConfigError::Yaml(e)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Json(e)
     }
 }
 
@@ -25,17 +92,139 @@ impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use ConfigError::*;
         match self {
+            Io(e) => write!(f, "IO error: {}", e),
+            Parse(e) => write!(f, "Parse error: {}", e),
+            Yaml(e) => write!(f, "Parse error: {}", e),
+            Json(e) => write!(f, "Parse error: {}", e),
             MissingKey(k) => write!(f, "Missing key: {}", k),
-            TypeError(t) => write!(f, "Type error: {}", t),
+            TypeError { message, .. } => write!(f, "Type error: {}", message),
+            UnknownKey { key, suggestion: Some(s) } => write!(f, "Unknown key: {} (did you mean '{}'?)", key, s),
+            UnknownKey { key, suggestion: None } => write!(f, "Unknown key: {}", key),
+            Inconsistent { message, .. } => write!(f, "Inconsistent config: {}", message),
+            InFile { diagnostics, .. } if f.alternate() => {
+                let shown: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+                write!(f, "{}", shown.join("\n\n"))
+            }
+            InFile { error, .. } => write!(f, "{}", error),
+            Invalid(problems) => {
+                write!(f, "{} problems in config:", problems.len())?;
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 impl Error for ConfigError {}
 
-pub fn load_config_from_file(config_path: &str) -> Result<AlbumTags, Box<dyn std::error::Error>> {
-    let config_file = fs::read_to_string(config_path)?;
-    let table = config_file.parse::<Table>()?;
-    let tags = AlbumTags::from_toml(table)?;
-    Ok(tags)
+/// The languages a config file can be written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// The format named by the extension of `path`, if it's one riptagger reads.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        extension.to_ascii_lowercase().parse().ok()
+    }
+
+    /// Parse `source` into the table the same config written in TOML would give.
+    pub fn parse(self, source: &str) -> Result<Table, ConfigError> {
+        match self {
+            ConfigFormat::Toml => Ok(source.parse::<Table>()?),
+            ConfigFormat::Yaml => Ok(serde_yaml::from_str(source)?),
+            ConfigFormat::Json => Ok(serde_json::from_str(source)?),
+        }
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(format!("unknown config format '{}', expected toml, yaml or json", s)),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFormat::Toml => write!(f, "toml"),
+            ConfigFormat::Yaml => write!(f, "yaml"),
+            ConfigFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Names an album's config is looked for under in its directory, in order.
+pub const CONFIG_FILE_NAMES: &[&str] = &[
+    "riptagger.toml",
+    "riptagger.yaml",
+    "riptagger.yml",
+    "riptagger.json",
+    ".riptagger.toml",
+    "album.toml",
+];
+
+/// The config in `directory`, the first of `CONFIG_FILE_NAMES` that's there.
+pub fn find_config_file(directory: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| directory.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The config file and album directory to use when either is left out: the
+/// config is looked for in the album directory, the album directory is the
+/// config's. With neither, the config is looked for in the current directory.
+/// `None` when no config is given or found.
+pub fn config_and_album_paths(config_path: Option<&str>, album_path: Option<&str>) -> Option<(String, String)> {
+    match (config_path, album_path) {
+        (Some(config_path), Some(album_path)) => Some((config_path.to_string(), album_path.to_string())),
+        (Some(config_path), None) => {
+            let album_path = match Path::new(config_path).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().to_string(),
+                _ => String::from("."),
+            };
+            Some((config_path.to_string(), album_path))
+        }
+        (None, album_path) => {
+            let album_path = album_path.unwrap_or(".");
+            let config_path = find_config_file(Path::new(album_path))?;
+            Some((config_path.to_string_lossy().to_string(), album_path.to_string()))
+        }
+    }
+}
+
+/// Load a config in the format named by its extension, TOML if the extension isn't a known one.
+pub fn load_config_from_file(config_path: &str) -> Result<AlbumTags, ConfigError> {
+    let format = ConfigFormat::from_path(config_path).unwrap_or_default();
+    load_config_from_file_as(config_path, format)
+}
+
+pub fn load_config_from_file_as(config_path: &str, format: ConfigFormat) -> Result<AlbumTags, ConfigError> {
+    load_resolved_config(config_path, format).map(|(tags, _)| tags)
+}
+
+/// Load a config with the files it extends and includes, which are kept to tell where each value came from.
+pub fn load_resolved_config(config_path: &str, format: ConfigFormat) -> Result<(AlbumTags, ResolvedConfig), ConfigError> {
+    let resolved = resolve_config(config_path, format)?;
+    let tags = AlbumTags::from_toml(resolved.table.clone()).map_err(|error| ConfigError::InFile {
+        path: config_path.to_string(),
+        diagnostics: resolved.diagnose(&error),
+        error: Box::new(error),
+    })?;
+    Ok((tags, resolved))
 }
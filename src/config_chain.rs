@@ -0,0 +1,233 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+//! Configs that build on other configs with `extends` and `include`.
+//!
+//! A config's values take precedence over those of its `include` files, later
+//! includes over earlier ones, and includes over the file it `extends`. Values
+//! are merged key by key at the top level: an array such as `tracks` or
+//! `genre` replaces the inherited one, it isn't appended to. Paths are relative
+//! to the file they're written in, as are the `picture` paths of each file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::config::{ConfigError, ConfigFormat};
+use crate::diagnostic::{diagnose, Diagnostic};
+use crate::fs_utils::resolve_config_path;
+
+/// A file read while resolving a config.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    pub path: String,
+    /// The text of the file.
+    pub source: String,
+    pub format: ConfigFormat,
+}
+
+/// A config merged with the files it extends and includes.
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub table: Table,
+    /// Every file read, the config itself first.
+    pub files: Vec<ConfigFile>,
+    /// Index in `files` of the file each top-level key was set in.
+    sources: BTreeMap<String, usize>,
+}
+
+impl ResolvedConfig {
+    /// The file the top-level key `key` was set in.
+    pub fn source_of(&self, key: &str) -> Option<&ConfigFile> {
+        self.sources.get(key).map(|i| &self.files[*i])
+    }
+
+    /// The keys each file other than the config itself contributed, in the order the files were read.
+    pub fn inherited(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut inherited = Vec::new();
+        for (i, file) in self.files.iter().enumerate().skip(1) {
+            let keys: Vec<&str> = self
+                .sources
+                .iter()
+                .filter(|(_, source)| **source == i)
+                .map(|(key, _)| key.as_str())
+                .collect();
+            if !keys.is_empty() {
+                inherited.push((file.path.as_str(), keys));
+            }
+        }
+        inherited
+    }
+
+    /// Point each problem in `error` at the file its key was set in. Problems
+    /// without a key, like a missing top-level key, point at the config itself.
+    pub fn diagnose(&self, error: &ConfigError) -> Vec<Diagnostic> {
+        let problems: Vec<&ConfigError> = match error {
+            ConfigError::Invalid(problems) => problems.iter().collect(),
+            error => vec![error],
+        };
+        problems
+            .into_iter()
+            .flat_map(|problem| {
+                let file = problem
+                    .key_path()
+                    .and_then(|path| {
+                        let key = path.split(['.', '[']).next().unwrap_or_default().to_string();
+                        self.source_of(&key)
+                    })
+                    .unwrap_or(&self.files[0]);
+                diagnose(&file.path, &file.source, file.format, problem)
+            })
+            .collect()
+    }
+}
+
+/// An error in the file `path`, unless it's already placed in a file further down the chain.
+fn in_file(path: &str, source: &str, format: ConfigFormat, error: ConfigError) -> ConfigError {
+    match error {
+        error @ ConfigError::InFile { .. } => error,
+        error => ConfigError::InFile {
+            path: path.to_string(),
+            diagnostics: diagnose(path, source, format, &error),
+            error: Box::new(error),
+        },
+    }
+}
+
+/// The files `table` extends and includes, removed from it so they aren't taken for config values.
+fn take_inherited(table: &mut Table) -> Result<(Option<String>, Vec<String>), ConfigError> {
+    let extends = match table.remove("extends") {
+        None => None,
+        Some(Value::String(path)) => Some(path),
+        Some(_) => {
+            return Err(ConfigError::TypeError {
+                key: String::from("extends"),
+                message: String::from("extends should be a string"),
+            })
+        }
+    };
+    let include = match table.remove("include") {
+        None => Vec::new(),
+        Some(Value::Array(paths)) => paths
+            .into_iter()
+            .map(|p| p.as_str().map(String::from))
+            .collect::<Option<_>>()
+            .ok_or_else(include_error)?,
+        Some(_) => return Err(include_error()),
+    };
+    Ok((extends, include))
+}
+
+fn include_error() -> ConfigError {
+    ConfigError::TypeError {
+        key: String::from("include"),
+        message: String::from("include should be an array of strings"),
+    }
+}
+
+/// Make the `picture` path of `table`, from a config in `dir`, relative to `dir`.
+fn resolve_picture_path(table: &mut Table, dir: &Path) {
+    if let Some(Value::String(path)) = table.get_mut("picture") {
+        *path = resolve_config_path(path, dir).to_string_lossy().to_string();
+    }
+}
+
+/// Make the album's and the discs' `picture` paths of a config in `dir` relative to `dir`.
+fn resolve_picture_paths(table: &mut Table, dir: &Path) {
+    resolve_picture_path(table, dir);
+    if let Some(Value::Array(discs)) = table.get_mut("disc") {
+        for disc in discs.iter_mut().filter_map(Value::as_table_mut) {
+            resolve_picture_path(disc, dir);
+        }
+    }
+}
+
+struct Resolver {
+    files: Vec<ConfigFile>,
+    /// The files being resolved, to catch a file that extends or includes itself.
+    chain: Vec<PathBuf>,
+}
+
+impl Resolver {
+    /// Errors reading or parsing the config itself are returned as they are, like
+    /// before it could extend other files. Those of inherited files name the file.
+    fn read_error(&self, path: &str, source: &str, format: ConfigFormat, error: ConfigError) -> ConfigError {
+        match self.chain.is_empty() {
+            true => error,
+            false => in_file(path, source, format, error),
+        }
+    }
+
+    /// The files `own`, read from `path`, extends and includes, in the order they're merged.
+    fn inherited_files(&self, path: &str, own: &mut Table, format: ConfigFormat) -> Result<Vec<(String, ConfigFormat)>, ConfigError> {
+        let (extends, include) = take_inherited(own)?;
+        let parent = Path::new(path).parent().unwrap_or(Path::new(""));
+        let mut inherited_files = Vec::new();
+        for (key, relative) in extends.iter().map(|p| ("extends", p)).chain(include.iter().map(|p| ("include", p))) {
+            let inherited = parent.join(relative);
+            if let Some(looped) = fs::canonicalize(&inherited).ok().filter(|p| self.chain.contains(p)) {
+                return Err(ConfigError::Inconsistent {
+                    key: key.to_string(),
+                    message: format!(
+                        "{} '{}' loops back to '{}', which is already being read",
+                        key,
+                        relative,
+                        looped.display()
+                    ),
+                });
+            }
+            let inherited = inherited.to_string_lossy().to_string();
+            let format = ConfigFormat::from_path(&inherited).unwrap_or(format);
+            inherited_files.push((inherited, format));
+        }
+        Ok(inherited_files)
+    }
+
+    /// The table of `path` merged over its `extends` and `include` files, with the file each key came from.
+    fn resolve(&mut self, path: &str, format: ConfigFormat) -> Result<(Table, BTreeMap<String, usize>), ConfigError> {
+        let source = fs::read_to_string(path).map_err(|e| self.read_error(path, "", format, e.into()))?;
+        let mut own = format.parse(&source).map_err(|e| self.read_error(path, &source, format, e))?;
+        let canonical = fs::canonicalize(path).map_err(|e| self.read_error(path, &source, format, e.into()))?;
+        let index = self.files.len();
+        self.files.push(ConfigFile {
+            path: path.to_string(),
+            source: source.clone(),
+            format,
+        });
+
+        self.chain.push(canonical);
+        let inherited_files = self
+            .inherited_files(path, &mut own, format)
+            .map_err(|e| in_file(path, &source, format, e))?;
+        let mut table = Table::new();
+        let mut sources = BTreeMap::new();
+        for (inherited, format) in inherited_files {
+            let (inherited_table, inherited_sources) = self.resolve(&inherited, format)?;
+            table.extend(inherited_table);
+            sources.extend(inherited_sources);
+        }
+        self.chain.pop();
+
+        resolve_picture_paths(&mut own, Path::new(path).parent().unwrap_or(Path::new("")));
+        sources.extend(own.keys().map(|key| (key.clone(), index)));
+        table.extend(own);
+        Ok((table, sources))
+    }
+}
+
+/// Read the config at `path`, in `format`, with the files it extends and includes.
+/// Those are read in the format their extension names, or in `format` if it names none.
+pub fn resolve_config(path: &str, format: ConfigFormat) -> Result<ResolvedConfig, ConfigError> {
+    let mut resolver = Resolver {
+        files: Vec::new(),
+        chain: Vec::new(),
+    };
+    let (table, sources) = resolver.resolve(path, format)?;
+    Ok(ResolvedConfig {
+        table,
+        files: resolver.files,
+        sources,
+    })
+}
@@ -0,0 +1,182 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::fmt;
+use std::ops::Range;
+
+use toml_edit::{ImDocument, Item};
+
+use crate::config::{ConfigError, ConfigFormat};
+
+/// A config error pointed at the place in the file it comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub file: String,
+    /// 1-based, `None` when the error has no place in the file, such as a missing top-level key.
+    pub line: Option<usize>,
+    pub column: usize,
+    /// The text of the line the error is on.
+    pub source_line: String,
+    /// Number of characters to highlight from `column`.
+    pub highlight_len: usize,
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.message)?;
+        match self.line {
+            Some(line) => {
+                let gutter = " ".repeat(line.to_string().len());
+                write!(f, "\n{}--> {}:{}:{}", gutter, self.file, line, self.column)?;
+                write!(f, "\n{} |", gutter)?;
+                write!(f, "\n{} | {}", line, self.source_line)?;
+                write!(
+                    f,
+                    "\n{} | {}{}",
+                    gutter,
+                    " ".repeat(self.column - 1),
+                    "^".repeat(self.highlight_len.max(1))
+                )?;
+                if let Some(hint) = &self.hint {
+                    write!(f, "\n{} = hint: {}", gutter, hint)?;
+                }
+            }
+            None => {
+                write!(f, "\n --> {}", self.file)?;
+                if let Some(hint) = &self.hint {
+                    write!(f, "\n  = hint: {}", hint)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One step of a key path such as `disc[0].track[2].title`.
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn parse_key_path(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let mut pieces = part.split('[');
+        if let Some(key) = pieces.next().filter(|k| !k.is_empty()) {
+            segments.push(Segment::Key(key));
+        }
+        for index in pieces {
+            if let Ok(i) = index.trim_end_matches(']').parse() {
+                segments.push(Segment::Index(i));
+            }
+        }
+    }
+    segments
+}
+
+/// Where a key path points in the document.
+enum Location {
+    /// The key itself, and its value.
+    Found { key: Option<Range<usize>>, value: Option<Range<usize>> },
+    /// The path stops at a nested table without the next key, the span of that table.
+    /// `None` for keys missing from the top level, which have no place in the file.
+    MissingIn(Option<Range<usize>>),
+}
+
+fn locate(doc: &ImDocument<&str>, path: &str) -> Location {
+    let mut item: &Item = doc.as_item();
+    let mut key_span = None;
+    for (depth, segment) in parse_key_path(path).into_iter().enumerate() {
+        let next = match segment {
+            Segment::Key(key) => item.as_table_like().and_then(|t| t.get_key_value(key)).map(|(k, v)| {
+                key_span = k.span();
+                v
+            }),
+            Segment::Index(i) => item.get(i).inspect(|_| key_span = None),
+        };
+        match next {
+            Some(next) => item = next,
+            None if depth == 0 => return Location::MissingIn(None),
+            None => return Location::MissingIn(item.span()),
+        }
+    }
+    Location::Found { key: key_span, value: item.span() }
+}
+
+/// The hint shown under a diagnostic, if there's more to say than the message.
+fn hint(error: &ConfigError) -> Option<String> {
+    match error {
+        ConfigError::MissingKey(key) => Some(match key.rsplit_once('.') {
+            Some((_, name)) => format!("add '{}' to this table", name),
+            None => format!("add '{}' to the config", key),
+        }),
+        ConfigError::UnknownKey { suggestion: None, .. } => {
+            Some(String::from("riptagger doesn't use this key, remove it or check its spelling"))
+        }
+        ConfigError::TypeError { message, .. } if message.ends_with("should be an integer") => {
+            Some(String::from("numbers are written without quotes, as in year = 2024"))
+        }
+        _ => None,
+    }
+}
+
+/// Byte offset to 1-based line and column, with the line's text.
+fn line_and_column(source: &str, offset: usize) -> (usize, usize, &str) {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    let line = source[..offset].matches('\n').count() + 1;
+    let column = source[line_start..offset].chars().count() + 1;
+    (line, column, source[line_start..line_end].trim_end_matches('\r'))
+}
+
+fn diagnostic(file: &str, source: &str, doc: Option<&ImDocument<&str>>, error: &ConfigError) -> Diagnostic {
+    let span = match (doc, error.key_path()) {
+        (Some(doc), Some(path)) => match (locate(doc, &path), error) {
+            (Location::Found { key, .. }, ConfigError::UnknownKey { .. }) => key,
+            (Location::Found { key, value }, _) => value.or(key),
+            (Location::MissingIn(table), _) => table,
+        },
+        _ => None,
+    };
+
+    let mut diagnostic = Diagnostic {
+        message: error.to_string(),
+        file: file.to_string(),
+        line: None,
+        column: 1,
+        source_line: String::new(),
+        highlight_len: 0,
+        hint: hint(error),
+    };
+    if let Some(span) = span {
+        let (line, column, text) = line_and_column(source, span.start);
+        // highlight to the end of the span, or of the line for spans over several lines
+        let highlight = source[span.start..span.end.min(source.len())].lines().next().unwrap_or("");
+        diagnostic.line = Some(line);
+        diagnostic.column = column;
+        diagnostic.source_line = text.to_string();
+        diagnostic.highlight_len = highlight.chars().count();
+    }
+    diagnostic
+}
+
+/// Point each problem in `error` at its place in `source`, the text of the config file `file`.
+/// Places are only looked up in TOML: a YAML or JSON config is turned into the
+/// same table, but its text can't be read as TOML, so its problems name just the file.
+pub fn diagnose(file: &str, source: &str, format: ConfigFormat, error: &ConfigError) -> Vec<Diagnostic> {
+    let doc = match format {
+        ConfigFormat::Toml => ImDocument::parse(source).ok(),
+        ConfigFormat::Yaml | ConfigFormat::Json => None,
+    };
+    match error {
+        ConfigError::Invalid(problems) => problems
+            .iter()
+            .map(|problem| diagnostic(file, source, doc.as_ref(), problem))
+            .collect(),
+        error => vec![diagnostic(file, source, doc.as_ref(), error)],
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::track_tags::TrackTags;
+
+/// The value of each field riptagger writes, formatted for display.
+pub fn tag_fields(tags: &TrackTags) -> Vec<(&'static str, Option<String>)> {
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    vec![
+        ("album", tags.album_name.clone()),
+        ("artist", tags.artist_name.clone()),
+        ("album artist", tags.album_artist.clone()),
+        ("composer", tags.composer.clone()),
+        ("title", non_empty(&tags.track_name)),
+        ("year", tags.year.map(|y| y.to_string())),
+        ("genre", non_empty(&tags.genre.join("; "))),
+        ("track number", tags.track_number.map(|n| n.to_string())),
+        ("track total", tags.track_total.map(|n| n.to_string())),
+        ("disc number", tags.disc_number.map(|n| n.to_string())),
+        ("disc total", tags.disc_total.map(|n| n.to_string())),
+        ("disc subtitle", tags.disc_subtitle.clone()),
+        ("position", tags.position.clone()),
+        ("compilation", tags.compilation.map(|c| String::from(if c { "yes" } else { "no" }))),
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// The fields that differ between `before` and `after`.
+pub fn field_changes(before: &TrackTags, after: &TrackTags) -> Vec<FieldChange> {
+    tag_fields(before)
+        .into_iter()
+        .zip(tag_fields(after))
+        .filter(|((_, b), (_, a))| b != a)
+        .map(|((field, before), (_, after))| FieldChange { field, before, after })
+        .collect()
+}
+
+/// What applying a `TrackPlan` would change, worked out without writing anything.
+#[derive(Debug, Clone)]
+pub struct TrackDiff {
+    pub path: PathBuf,
+    pub changes: Vec<FieldChange>,
+    /// The cover picture that would be embedded.
+    pub picture_path: Option<String>,
+    pub new_name: Option<String>,
+}
+
+impl TrackDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.picture_path.is_none() && self.new_name.is_none()
+    }
+}
+
+fn show(value: &Option<String>) -> String {
+    match value {
+        Some(v) => format!("{:?}", v),
+        None => String::from("(none)"),
+    }
+}
+
+impl fmt::Display for TrackDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if self.is_empty() {
+            return write!(f, "\n  no changes");
+        }
+        for change in &self.changes {
+            write!(f, "\n  {}: {} -> {}", change.field, show(&change.before), show(&change.after))?;
+        }
+        if let Some(picture_path) = &self.picture_path {
+            write!(f, "\n  front cover: embed '{}'", picture_path)?;
+        }
+        if let Some(new_name) = &self.new_name {
+            write!(f, "\n  rename to '{}'", new_name)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::{ConfigError, CONFIG_FILE_NAMES};
+use crate::journal::JournalError;
+use crate::pipeline::{ApplyError, PlanError};
+use crate::track_tags::TagError;
+
+/// Process exit codes, one per class of failure. These start at 3, as clap
+/// exits with 2 on a usage error and 1 is the usual catch-all. A panic exits
+/// with 101.
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    pub const CONFIG: i32 = 3;
+    pub const DISCOVERY: i32 = 4;
+    pub const TAG: i32 = 5;
+    pub const PICTURE: i32 = 6;
+    pub const RENAME: i32 = 7;
+    pub const JOURNAL: i32 = 8;
+}
+
+#[derive(Debug)]
+pub enum RiptaggerError {
+    Config { path: String, error: ConfigError },
+    /// No config file was given, and none was found in the album directory.
+    NoConfig { directory: String },
+    /// Finding the album's audio files or matching them to the configured tracks
+    /// failed, the cover picture is too big to embed, or a track's new name is taken.
+    Discovery(PlanError),
+    Tag { path: PathBuf, error: TagError },
+    Picture { path: PathBuf, error: TagError },
+    Rename { path: PathBuf, error: io::Error },
+    /// Writing the undo journal, or undoing a run, failed.
+    Journal(JournalError),
+    /// Some tracks failed, the rest were tagged.
+    TracksFailed { failed: Vec<RiptaggerError>, total: usize },
+}
+
+impl RiptaggerError {
+    pub fn config(path: &str, error: ConfigError) -> Self {
+        RiptaggerError::Config {
+            path: path.to_string(),
+            error,
+        }
+    }
+
+    /// Classify the failure of one track.
+    pub fn track(path: PathBuf, error: ApplyError) -> Self {
+        match error {
+            ApplyError::Tag(error @ TagError::Picture(..)) => RiptaggerError::Picture { path, error },
+            ApplyError::Tag(error) => RiptaggerError::Tag { path, error },
+            ApplyError::Rename(error) => RiptaggerError::Rename { path, error },
+            ApplyError::Journal(error) => RiptaggerError::Journal(error),
+        }
+    }
+
+    /// The process exit code for this error. When several tracks failed,
+    /// the code of the first failure is used.
+    pub fn exit_code(&self) -> i32 {
+        use RiptaggerError::*;
+        match self {
+            Config { .. } | NoConfig { .. } => exit_code::CONFIG,
+            Discovery(PlanError::PictureTooLarge { .. }) => exit_code::PICTURE,
+            Discovery(PlanError::NameTaken { .. } | PlanError::DuplicateName { .. }) => exit_code::RENAME,
+            Discovery(_) => exit_code::DISCOVERY,
+            Tag { .. } => exit_code::TAG,
+            Picture { .. } => exit_code::PICTURE,
+            Rename { .. } => exit_code::RENAME,
+            Journal(_) => exit_code::JOURNAL,
+            TracksFailed { failed, .. } => failed.first().map_or(exit_code::TAG, |e| e.exit_code()),
+        }
+    }
+}
+
+impl From<PlanError> for RiptaggerError {
+    fn from(e: PlanError) -> Self {
+        RiptaggerError::Discovery(e)
+    }
+}
+
+impl From<JournalError> for RiptaggerError {
+    fn from(e: JournalError) -> Self {
+        RiptaggerError::Journal(e)
+    }
+}
+
+impl fmt::Display for RiptaggerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RiptaggerError::*;
+        match self {
+            // the alternate form shows where in the file the problems are
+            Config { path, error } if f.alternate() => write!(f, "Could not read config file '{}':\n{:#}", path, error),
+            Config { path, error } => write!(f, "Could not read config file '{}': {}", path, error),
+            NoConfig { directory } => write!(
+                f,
+                "No config file given and none found in '{}', looked for {}",
+                directory,
+                CONFIG_FILE_NAMES.join(", ")
+            ),
+            Discovery(e) => write!(f, "{}", e),
+            Tag { path, error } => write!(f, "Error tagging '{}': {}", path.display(), error),
+            Picture { path, error } => write!(f, "Error tagging '{}': {}", path.display(), error),
+            Rename { path, error } => write!(f, "Error renaming file '{}': {}", path.display(), error),
+            Journal(e) => write!(f, "Undo journal: {}", e),
+            TracksFailed { failed, total } => write!(f, "{} of {} tracks failed", failed.len(), total),
+        }
+    }
+}
+
+impl Error for RiptaggerError {}
@@ -0,0 +1,53 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use metaflac::block::PictureType::CoverFront;
+use metaflac::Tag;
+
+use crate::tag_writer::{CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{track_tags_from_vorbis, vorbis_comments, TagError, TrackTags};
+
+/// Vorbis comments and a PICTURE block in a native FLAC stream.
+pub struct FlacWriter;
+
+struct FlacFile {
+    tag: Tag,
+}
+
+impl TagWriter for FlacWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        Ok(Box::new(FlacFile {
+            tag: Tag::read_from_path(track_path)?,
+        }))
+    }
+}
+
+impl TrackFile for FlacFile {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        let get = |key: &str| {
+            self.tag
+                .get_vorbis(key)
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_default()
+        };
+        track_tags_from_vorbis(get, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        for (key, values) in vorbis_comments(tags) {
+            self.tag.set_vorbis(key, values);
+        }
+
+        if let Some(cover) = CoverPicture::from_tags(tags)? {
+            // replaces any existing front cover
+            self.tag.add_picture(cover.mime_type, CoverFront, cover.data);
+        }
+
+        Ok(())
+    }
+
+    fn save(mut self: Box<Self>) -> Result<(), TagError> {
+        self.tag.save()?;
+        Ok(())
+    }
+}
@@ -2,31 +2,30 @@
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 
 use std::ffi::OsStr;
-use std::fs::{DirEntry, read_dir, rename};
-use std::io;
+use std::fs::{DirEntry, read_dir};
 use std::path::{Path, PathBuf};
 use std::collections::HashSet;
 
 use once_cell::sync::Lazy;
 
+use crate::audio_format::{sniff_audio_format, AudioFormat};
+
 fn get_file_extension(file_name: &OsStr) -> Option<&str> {
     Path::new(file_name)
         .extension()
         .and_then(|ext| ext.to_str())
 }
 
+/// Files are matched on an audio extension, in any case. Files without an
+/// extension are matched on their contents, files with any other extension,
+/// like logs, cue sheets and pictures, are never tracks.
 fn is_audio_file(f: &DirEntry) -> bool {
+    if !f.file_type().is_ok_and(|t| t.is_file()) {
+        return false;
+    }
     match get_file_extension(&f.file_name()) {
-        Some(ext) => {
-            match ext {
-                "mp3" => true,
-                "ogg" => true,
-                "flac" => true,
-                "asf" => true,
-                _ => false,
-            }
-        },
-        None => false
+        Some(extension) => AudioFormat::from_extension(extension).is_some(),
+        None => matches!(sniff_audio_format(&f.path()), Ok(Some(_))),
     }
 }
 
@@ -39,7 +38,7 @@ pub fn get_audio_files_in_directory(dir_path: &str) -> Result<Vec<DirEntry>, std
                 .collect())
         }
         Err(e) => {
-            return Err(e);
+            Err(e)
         }
     }
 }
@@ -56,29 +55,94 @@ pub fn normalize(s: &str) -> String {
         .filter(|c| !DISALLOWED_CHARS.contains(c)).collect::<String>()
 }
 
-pub fn rename_audio_file<T: Into<i64>>(file_path: &str, track_number: T, track_name: &str) -> std::io::Result<String> {
-    let parent = Path::new(file_path).parent().unwrap_or(Path::new("."));
-    // original file extension, with leading .
-    let extension = Path::new(file_path)
+/// The extension of `file_path`, with leading .
+fn extension_of(file_path: &str) -> String {
+    Path::new(file_path)
         .extension()
         .and_then(|osstr| osstr.to_str())
-        .and_then(|s| format!(".{}", s).into());
+        .map(|s| format!(".{}", s))
+        .unwrap_or(String::from(".unknown"))
+}
+
+/// 'position - track name', keeping the extension of `file_path`, as in 'A1 - Title' for a vinyl track.
+pub fn file_name_at(file_path: &str, position: &str, track_name: &str) -> String {
+    format!(
+        "{} - {}{}",
+        normalize(position),
+        normalize(track_name),
+        extension_of(file_path))
+}
+
+/// The track number part of a file name, 'track number' or 'disc-track number'.
+pub fn track_label(disc_number: Option<i64>, track_number: i64) -> String {
     // zero-pad track number to at least 2 digits
     // there may be some *long* album out there with >99 tracks.
     // I only plan on using this for tagging CD rips, unlikely this will ever be a problem.
-    let new_name = format!(
-        "{:#02} - {}{}",
-        track_number.into(),
-        normalize(track_name),
-        extension.unwrap_or(String::from(".unknown")));
-    let new_path = parent.join(&new_name);
+    let disc_prefix = disc_number.map(|d| format!("{}-", d)).unwrap_or_default();
+    format!("{}{:#02}", disc_prefix, track_number)
+}
+
+/// A file name from `template`, keeping the extension of `file_path`. Each
+/// `{name}` in the template is replaced by the value `fields` gives `name`,
+/// placeholders without one are left as written.
+pub fn templated_file_name(file_path: &str, template: &str, fields: &[(&str, String)]) -> String {
+    let mut name = template.to_string();
+    for (field, value) in fields {
+        name = name.replace(&format!("{{{}}}", field), &normalize(value));
+    }
+    format!("{}{}", name, extension_of(file_path))
+}
+
+/// The 'track number - track name' file name for a track, keeping its extension.
+/// With a disc number the name starts 'disc-track number', as in '1-01 - Title'.
+pub fn audio_file_name<T: Into<i64>>(file_path: &str, disc_number: Option<i64>, track_number: T, track_name: &str) -> String {
+    file_name_at(file_path, &track_label(disc_number, track_number.into()), track_name)
+}
+
+/// A hidden name in the directory of `path` to move a file to while it's
+/// renamed, `n` telling apart the files moved at once.
+pub fn temporary_path(path: &Path, n: usize) -> PathBuf {
+    path.with_file_name(format!(".riptagger-{}.tmp", n))
+}
+
+/// Names of the pictures looked for in an album directory when the config has
+/// none, in order of preference, matched without their extension.
+pub const COVER_PICTURE_NAMES: &[&str] = &["cover", "folder", "front"];
+
+const PICTURE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif"];
+
+/// The cover picture that came with a rip in `dir`, the first of `COVER_PICTURE_NAMES` there.
+pub fn find_cover_picture(dir: &Path) -> Option<PathBuf> {
+    let mut pictures: Vec<PathBuf> = read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            get_file_extension(path.as_os_str()).is_some_and(|e| PICTURE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    pictures.sort();
+    COVER_PICTURE_NAMES.iter().find_map(|name| {
+        pictures
+            .iter()
+            .find(|path| path.file_stem().and_then(OsStr::to_str).is_some_and(|stem| stem.eq_ignore_ascii_case(name)))
+            .cloned()
+    })
+}
 
-    match rename(file_path, new_path) {
-        Ok(_) => Ok(new_name),
-        Err(e) => Err(e),
+/// `path` with a leading `~` replaced by the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match (path.strip_prefix('~'), home) {
+        (Some(""), Some(home)) => home,
+        (Some(rest), Some(home)) if rest.starts_with(std::path::is_separator) => home.join(&rest[1..]),
+        _ => PathBuf::from(path),
     }
 }
 
-pub fn get_current_directory() -> io::Result<PathBuf> {
-    std::env::current_dir()
+/// A path written in a config file in `dir`: `~` is the home directory, and
+/// a relative path is relative to `dir`.
+pub fn resolve_config_path(path: &str, dir: &Path) -> PathBuf {
+    dir.join(expand_home(path))
 }
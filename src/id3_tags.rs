@@ -0,0 +1,214 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use id3::frame::{ExtendedText, Picture, PictureType, Timestamp};
+use id3::{Tag, TagLike, Version};
+
+use crate::tag_writer::{parse_flag, CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{TagError, TrackTags, DEFAULT_POSITION_TAG};
+
+/// Description of the TXXX frame ID3v2.3 tags hold the disc subtitle in.
+const DISC_SUBTITLE_DESCRIPTION: &str = "DISCSUBTITLE";
+
+/// Read the ID3v2 tag of a file, starting from an empty tag if it has none.
+pub fn read_id3_tag(track_path: &str) -> Result<Tag, TagError> {
+    match id3::no_tag_ok(Tag::read_from_path(track_path))? {
+        Some(tag) => Ok(tag),
+        None => Ok(Tag::new()),
+    }
+}
+
+/// Copy `tags` into the ID3v2 frames of `tag`.
+pub fn apply_id3_frames(tags: &TrackTags, tag: &mut Tag) -> Result<(), TagError> {
+    if let Some(album_name) = &tags.album_name {
+        tag.set_album(album_name);
+    }
+
+    if let Some(artist_name) = &tags.artist_name {
+        tag.set_artist(artist_name);
+    }
+
+    if let Some(album_artist) = &tags.album_artist {
+        tag.set_album_artist(album_artist);
+    }
+
+    if let Some(composer) = &tags.composer {
+        tag.set_text("TCOM", composer);
+    }
+
+    tag.set_title(&tags.track_name);
+
+    // TRCK and TPOS are written as "n/m" by the id3 crate
+    if let Some(track_number) = tags.track_number {
+        tag.set_track(track_number as u32);
+    }
+
+    if let Some(track_total) = tags.track_total {
+        tag.set_total_tracks(track_total as u32);
+    }
+
+    if let Some(year) = tags.year {
+        tag.set_date_recorded(Timestamp {
+            year: year as i32,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+        });
+    }
+
+    if !tags.genre.is_empty() {
+        tag.set_text_values("TCON", tags.genre.clone());
+    }
+
+    if let Some(disc_num) = tags.disc_number {
+        tag.set_disc(disc_num as u32);
+    }
+
+    if let Some(disc_total) = tags.disc_total {
+        tag.set_total_discs(disc_total as u32);
+    }
+
+    if let Some(disc_subtitle) = &tags.disc_subtitle {
+        tag.set_text("TSST", disc_subtitle);
+        // left behind if the file was last written as ID3v2.3
+        tag.remove_extended_text(Some(DISC_SUBTITLE_DESCRIPTION), None);
+    }
+
+    if let Some(position) = &tags.position {
+        // replaces any TXXX frame with the same description
+        tag.add_frame(ExtendedText {
+            description: tags.position_key().to_string(),
+            value: position.clone(),
+        });
+    }
+
+    // TCMP is an iTunes extension, but widely read
+    match tags.compilation {
+        Some(true) => tag.set_text("TCMP", "1"),
+        Some(false) => {
+            tag.remove("TCMP");
+        }
+        None => (),
+    }
+
+    if let Some(cover) = CoverPicture::from_tags(tags)? {
+        tag.remove_picture_by_type(PictureType::CoverFront);
+        tag.add_frame(Picture {
+            mime_type: cover.mime_type.to_string(),
+            picture_type: PictureType::CoverFront,
+            description: String::new(),
+            data: cover.data,
+        });
+    }
+
+    Ok(())
+}
+
+/// Replace frames ID3v2.3 doesn't have with their ID3v2.3 equivalents, which the
+/// id3 crate leaves to the caller. The year is written to TYER in place of TDRC,
+/// and the disc subtitle to a `DISCSUBTITLE` TXXX frame in place of TSST.
+pub fn downgrade_to_id3v23(tag: &mut Tag) {
+    if let Some(date) = tag.date_recorded() {
+        tag.remove_date_recorded();
+        tag.set_year(date.year);
+    }
+    if let Some(disc_subtitle) = tag.remove("TSST").first().and_then(|frame| frame.content().text()) {
+        tag.add_frame(ExtendedText {
+            description: DISC_SUBTITLE_DESCRIPTION.to_string(),
+            value: disc_subtitle.to_string(),
+        });
+    }
+}
+
+/// Map the frames of `tag` back onto `TrackTags`.
+pub fn track_tags_from_id3(tag: &Tag, position_tag: Option<&str>) -> TrackTags {
+    let position_key = position_tag.unwrap_or(DEFAULT_POSITION_TAG);
+    TrackTags {
+        album_name: tag.album().map(String::from),
+        artist_name: tag.artist().map(String::from),
+        album_artist: tag.album_artist().map(String::from),
+        composer: tag.text_for_frame_id("TCOM").map(String::from),
+        // TDRC, falling back to the ID3v2.3 TYER frame
+        year: tag
+            .date_recorded()
+            .map(|date| date.year as i64)
+            .or(tag.year().map(|year| year as i64)),
+        track_name: tag.title().unwrap_or_default().to_string(),
+        genre: tag
+            .genres()
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        picture_path: None,
+        track_number: tag.track().map(|n| n as i64),
+        track_total: tag.total_tracks().map(|n| n as i64),
+        disc_number: tag.disc().map(|n| n as i64),
+        disc_total: tag.total_discs().map(|n| n as i64),
+        // TSST, falling back to the TXXX frame written for ID3v2.3
+        disc_subtitle: tag
+            .text_for_frame_id("TSST")
+            .or(tag
+                .extended_texts()
+                .find(|t| t.description == DISC_SUBTITLE_DESCRIPTION)
+                .map(|t| t.value.as_str()))
+            .map(String::from),
+        position: tag
+            .extended_texts()
+            .find(|t| t.description == position_key)
+            .map(|t| t.value.clone()),
+        position_tag: position_tag.map(String::from),
+        compilation: tag.text_for_frame_id("TCMP").map(parse_flag),
+    }
+}
+
+/// An ID3v2 tag, at the start of an MP3 or in the ID3 chunk of an AIFF file.
+pub struct Id3Writer {
+    /// The version tags are written as, whatever version the file had.
+    pub version: Version,
+}
+
+impl Default for Id3Writer {
+    fn default() -> Self {
+        Id3Writer {
+            version: Version::Id3v24,
+        }
+    }
+}
+
+struct Id3File {
+    path: String,
+    tag: Tag,
+    version: Version,
+}
+
+impl TagWriter for Id3Writer {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        Ok(Box::new(Id3File {
+            path: track_path.to_string(),
+            tag: read_id3_tag(track_path)?,
+            version: self.version,
+        }))
+    }
+}
+
+impl TrackFile for Id3File {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_id3(&self.tag, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        apply_id3_frames(tags, &mut self.tag)
+    }
+
+    fn save(mut self: Box<Self>) -> Result<(), TagError> {
+        if self.version == Version::Id3v23 {
+            downgrade_to_id3v23(&mut self.tag);
+        }
+        // the id3 crate picks the MP3 or AIFF layout from the file contents
+        self.tag.write_to_path(&self.path, self.version)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{create_dir_all, read, read_to_string, remove_dir_all, rename, write};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use toml::{Table, Value};
+
+use crate::fs_utils::temporary_path;
+
+/// Directory inside the album directory holding the journal of the last run.
+pub const JOURNAL_DIR: &str = ".riptagger-journal";
+/// Directory the journal of a run is written to until the run finishes.
+pub const PENDING_JOURNAL_DIR: &str = ".riptagger-journal.pending";
+const JOURNAL_FILE: &str = "journal.toml";
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+    /// There is no journal in the album directory.
+    NotFound(PathBuf),
+    /// The file was changed after riptagger tagged it, restoring it would lose those changes.
+    Modified(PathBuf),
+    /// An earlier run in the album directory was interrupted, its journal is still pending.
+    Interrupted(PathBuf),
+}
+
+impl From<io::Error> for JournalError {
+    fn from(e: io::Error) -> Self {
+        JournalError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for JournalError {
+    fn from(e: toml::de::Error) -> Self {
+        JournalError::Parse(e)
+    }
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use JournalError::*;
+        match self {
+            Io(e) => write!(f, "IO error: {}", e),
+            Parse(e) => write!(f, "Parse error: {}", e),
+            Invalid(msg) => write!(f, "Invalid journal: {}", msg),
+            NotFound(dir) => write!(f, "No journal found in '{}'", dir.display()),
+            Modified(path) => write!(f, "'{}' has changed since it was tagged", path.display()),
+            Interrupted(dir) => write!(
+                f,
+                "The last run in '{}' was interrupted, undo it before tagging again",
+                dir.display()
+            ),
+        }
+    }
+}
+
+impl Error for JournalError {}
+
+/// FNV-1a, to notice files that changed after tagging.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The bytes a tag write changed: the original file is the tagged file with
+/// `tagged[prefix_len..tagged_len - suffix_len]` replaced by the backup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedRegion {
+    pub prefix_len: u64,
+    pub suffix_len: u64,
+    pub tagged_len: u64,
+    pub tagged_checksum: u64,
+}
+
+/// One file touched by a run. File names are relative to the album directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub original_name: String,
+    pub new_name: String,
+    /// Unix time the file was first touched.
+    pub timestamp: u64,
+    /// File in the journal directory holding the original bytes.
+    pub backup: String,
+    /// `None` until the tagged file is written, the backup is then the whole original file.
+    pub region: Option<TaggedRegion>,
+}
+
+impl JournalEntry {
+    fn to_toml(&self) -> Table {
+        let mut table = Table::new();
+        table.insert("original_name".into(), Value::String(self.original_name.clone()));
+        table.insert("new_name".into(), Value::String(self.new_name.clone()));
+        table.insert("timestamp".into(), Value::Integer(self.timestamp as i64));
+        table.insert("backup".into(), Value::String(self.backup.clone()));
+        if let Some(region) = &self.region {
+            table.insert("prefix_len".into(), Value::Integer(region.prefix_len as i64));
+            table.insert("suffix_len".into(), Value::Integer(region.suffix_len as i64));
+            table.insert("tagged_len".into(), Value::Integer(region.tagged_len as i64));
+            // stored as a string, TOML integers are signed
+            table.insert("tagged_checksum".into(), Value::String(format!("{:016x}", region.tagged_checksum)));
+        }
+        table
+    }
+
+    fn from_toml(table: &Table) -> Result<Self, JournalError> {
+        let string = |key: &str| {
+            table
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| JournalError::Invalid(format!("missing {}", key)))
+        };
+        let integer = |key: &str| {
+            table
+                .get(key)
+                .and_then(|v| v.as_integer())
+                .map(|i| i as u64)
+                .ok_or_else(|| JournalError::Invalid(format!("missing {}", key)))
+        };
+        let region = match table.contains_key("tagged_len") {
+            true => Some(TaggedRegion {
+                prefix_len: integer("prefix_len")?,
+                suffix_len: integer("suffix_len")?,
+                tagged_len: integer("tagged_len")?,
+                tagged_checksum: u64::from_str_radix(&string("tagged_checksum")?, 16)
+                    .map_err(|_| JournalError::Invalid(String::from("bad checksum")))?,
+            }),
+            false => None,
+        };
+        Ok(JournalEntry {
+            original_name: string("original_name")?,
+            new_name: string("new_name")?,
+            timestamp: integer("timestamp")?,
+            backup: string("backup")?,
+            region,
+        })
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// A record of the changes made by one run, kept up to date on disk as the run
+/// progresses so an interrupted run can still be undone.
+#[derive(Debug)]
+pub struct Journal {
+    dir: PathBuf,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Start the journal of a new run in `album_dir`. The journal of the last
+    /// run is kept until this one is finished, so a run that fails part way
+    /// can still be undone.
+    pub fn begin(album_dir: &Path) -> Result<Self, JournalError> {
+        let dir = album_dir.join(PENDING_JOURNAL_DIR);
+        if let Some(pending) = Journal::read(&dir)? {
+            // a run interrupted before it changed anything left nothing to undo
+            if !pending.entries.is_empty() {
+                return Err(JournalError::Interrupted(album_dir.to_path_buf()));
+            }
+            remove_dir_all(&dir)?;
+        }
+        create_dir_all(&dir)?;
+        let journal = Journal {
+            dir,
+            entries: Vec::new(),
+        };
+        journal.save()?;
+        Ok(journal)
+    }
+
+    /// Make this the journal of the last run, replacing the one before. A run
+    /// that changed nothing leaves the journal before in place.
+    pub fn finish(self) -> Result<(), JournalError> {
+        if self.entries.is_empty() {
+            remove_dir_all(&self.dir)?;
+            return Ok(());
+        }
+        let finished = self.dir.with_file_name(JOURNAL_DIR);
+        if finished.exists() {
+            remove_dir_all(&finished)?;
+        }
+        rename(&self.dir, finished)?;
+        Ok(())
+    }
+
+    /// The journal `undo` restores from: that of an interrupted run if there is one, or else the last run's.
+    pub fn open(album_dir: &Path) -> Result<Self, JournalError> {
+        if let Some(pending) = Journal::read(&album_dir.join(PENDING_JOURNAL_DIR))?
+            && !pending.entries.is_empty()
+        {
+            return Ok(pending);
+        }
+        Journal::read(&album_dir.join(JOURNAL_DIR))?.ok_or_else(|| JournalError::NotFound(album_dir.to_path_buf()))
+    }
+
+    /// The journal in `dir`, `None` if there's none there.
+    fn read(dir: &Path) -> Result<Option<Self>, JournalError> {
+        let contents = match read_to_string(dir.join(JOURNAL_FILE)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let table = contents.parse::<Table>()?;
+        let entries = match table.get("entry") {
+            Some(Value::Array(entries)) => entries
+                .iter()
+                .map(|e| match e {
+                    Value::Table(t) => JournalEntry::from_toml(t),
+                    _ => Err(JournalError::Invalid(String::from("entry is not a table"))),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
+        Ok(Some(Journal {
+            dir: dir.to_path_buf(),
+            entries,
+        }))
+    }
+
+    fn save(&self) -> Result<(), JournalError> {
+        let mut table = Table::new();
+        table.insert("created".into(), Value::Integer(now() as i64));
+        let entries = self.entries.iter().map(|e| Value::Table(e.to_toml())).collect();
+        table.insert("entry".into(), Value::Array(entries));
+        write(self.dir.join(JOURNAL_FILE), table.to_string())?;
+        Ok(())
+    }
+
+    /// Back up a file before it's tagged. Returns the index of its entry.
+    pub fn record_original(&mut self, path: &Path, original: &[u8]) -> Result<usize, JournalError> {
+        let index = self.entries.len();
+        let backup = format!("{}.orig", index);
+        write(self.dir.join(&backup), original)?;
+        self.entries.push(JournalEntry {
+            original_name: file_name(path),
+            new_name: file_name(path),
+            timestamp: now(),
+            backup,
+            region: None,
+        });
+        self.save()?;
+        Ok(index)
+    }
+
+    /// Shrink the backup of entry `index` down to the bytes the tag write changed.
+    pub fn record_tagged(&mut self, index: usize, original: &[u8], tagged: &[u8]) -> Result<(), JournalError> {
+        let prefix_len = original
+            .iter()
+            .zip(tagged)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = original.len().min(tagged.len()) - prefix_len;
+        let suffix_len = original
+            .iter()
+            .rev()
+            .zip(tagged.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let entry = &mut self.entries[index];
+        write(self.dir.join(&entry.backup), &original[prefix_len..original.len() - suffix_len])?;
+        entry.region = Some(TaggedRegion {
+            prefix_len: prefix_len as u64,
+            suffix_len: suffix_len as u64,
+            tagged_len: tagged.len() as u64,
+            tagged_checksum: checksum(tagged),
+        });
+        self.save()
+    }
+
+    /// Note the name entry `index` is about to be renamed to.
+    pub fn record_rename(&mut self, index: usize, new_path: &Path) -> Result<(), JournalError> {
+        self.entries[index].new_name = file_name(new_path);
+        self.save()
+    }
+}
+
+/// Restore every file recorded in the journal of `album_dir` to its state
+/// before the last run, then remove the journal. Returns the restored paths.
+pub fn undo(album_dir: &Path) -> Result<Vec<PathBuf>, JournalError> {
+    let journal = Journal::open(album_dir)?;
+
+    // check everything before touching anything
+    let mut restores = Vec::new();
+    for entry in journal.entries.iter().rev() {
+        let new_path = album_dir.join(&entry.new_name);
+        let original_path = album_dir.join(&entry.original_name);
+        // the run may have stopped before the rename
+        let current_path = if new_path.exists() { new_path } else { original_path.clone() };
+        let current = read(&current_path)?;
+        let backup = read(journal.dir.join(&entry.backup))?;
+
+        let original = match &entry.region {
+            Some(region) => {
+                if current.len() as u64 != region.tagged_len || checksum(&current) != region.tagged_checksum {
+                    return Err(JournalError::Modified(current_path));
+                }
+                let suffix_start = current.len() - region.suffix_len as usize;
+                let mut original = current[..region.prefix_len as usize].to_vec();
+                original.extend(backup);
+                original.extend(&current[suffix_start..]);
+                original
+            }
+            None => backup,
+        };
+        restores.push((current_path, original_path, original));
+    }
+
+    // files may have taken each other's names, so renamed files are moved
+    // out of the way before any gets its original name back
+    let mut renames = Vec::new();
+    for (i, (current_path, original_path, original)) in restores.into_iter().enumerate() {
+        write(&current_path, original)?;
+        if current_path != original_path {
+            let temporary = temporary_path(&current_path, i);
+            rename(&current_path, &temporary)?;
+            renames.push((temporary, original_path));
+        } else {
+            renames.push((current_path, original_path));
+        }
+    }
+    let mut restored = Vec::new();
+    for (current_path, original_path) in renames {
+        if current_path != original_path {
+            rename(&current_path, &original_path)?;
+        }
+        restored.push(original_path);
+    }
+    remove_dir_all(&journal.dir)?;
+    Ok(restored)
+}
@@ -0,0 +1,53 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+//! Tag a directory of ripped audio files from a TOML, YAML or JSON description
+//! of the album.
+//!
+//! The pipeline has three steps: load an [`Album`], turn it into one
+//! [`TrackPlan`] per file with [`Album::plan`], then write each plan with
+//! [`TrackPlan::apply`] or [`apply`].
+//!
+//! ```no_run
+//! use riptagger::{apply, Album, PlanOptions};
+//! use riptagger::tag_writer::TagWriterRegistry;
+//!
+//! let album = Album::from_config_file("album.toml", "rips/romance")?;
+//! let plans = album.plan(&PlanOptions::default())?;
+//! for result in apply(&plans, &TagWriterRegistry::default()) {
+//!     result?;
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod album_config;
+pub mod config;
+pub mod config_chain;
+pub mod track_tags;
+pub mod album_tags;
+pub mod fs_utils;
+pub(crate) mod flac_tags;
+pub(crate) mod ape_tags;
+pub mod audio_format;
+pub(crate) mod diagnostic;
+pub mod diff;
+pub mod error;
+pub(crate) mod asf_tags;
+pub(crate) mod id3_tags;
+pub mod journal;
+pub(crate) mod mp4_tags;
+pub(crate) mod ogg_tags;
+pub mod pipeline;
+pub(crate) mod riff_tags;
+pub mod settings;
+pub mod tag_writer;
+pub(crate) mod validation;
+#[cfg(test)]
+mod tests;
+
+pub use config::ConfigError;
+pub use diagnostic::Diagnostic;
+pub use error::RiptaggerError;
+pub use journal::JournalError;
+pub use pipeline::{apply, apply_with_journal, Album, ApplyError, PlanError, PlanOptions, TrackPlan};
+pub use track_tags::TagError;
@@ -1,88 +1,233 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 
-pub mod config;
-pub mod toml_helpers;
-pub mod track_tags;
-pub mod album_tags;
-pub mod fs_utils;
-pub mod tests;
-use clap::Parser;
-
-use config::load_config_from_file;
-use track_tags::{assign_tags_to_track, TrackTags};
-use album_tags::{to_track_tags, AlbumTags};
-use fs_utils::{get_audio_files_in_directory, rename_audio_file};
+use std::path::Path;
+use std::process::exit;
+
+use clap::{Parser, Subcommand};
+
+use riptagger::album_config::album_config_schema;
+use riptagger::album_tags::AlbumTags;
+use riptagger::config::{config_and_album_paths, load_resolved_config, ConfigFormat};
+use riptagger::config_chain::ResolvedConfig;
+use riptagger::journal::{undo, Journal};
+use riptagger::{apply_with_journal, Album, ApplyError, PlanOptions, RiptaggerError};
+use riptagger::settings::{load_user_settings, user_config_path, Settings, SettingsConfig};
+use riptagger::tag_writer::TagWriterRegistry;
 
 static ABOUT: &str = "Copyright (C) 2025 Colm Murphy
 GPLv3: GNU GPL version 3 or later <https://www.gnu.org/licenses/gpl.html>
 This is free software: you are free to change and redistribute it.
 There is NO WARRANTY, to the extent permitted by law.";
 
+static EXIT_CODES: &str = "Exit codes:
+  0    all tracks tagged
+  1    not used, left as the usual catch-all
+  2    the command line could not be parsed
+  3    the config file could not be found or read
+  4    the album's audio files could not be found or matched to the config
+  5    a track could not be tagged
+  6    a cover picture could not be read, or is over max_picture_size
+  7    a track could not be renamed
+  8    the undo journal could not be written, or undo failed
+  101  riptagger panicked
+When several tracks fail, the exit code is that of the first failure.";
+
 #[derive(Parser, Debug)]
-#[command(version, about=ABOUT, long_about = None)]
+#[command(version, about=ABOUT, long_about = None, after_help=EXIT_CODES)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Album config file. Looked for in the album directory when not given
     #[arg(short, long)]
-    config_path: String,
+    config_path: Option<String>,
+
+    /// Format of the config file: toml, yaml or json. Taken from its extension when not given
+    #[arg(long)]
+    config_format: Option<ConfigFormat>,
 
+    /// Directory of the album's audio files. The config file's directory when not given
     #[arg(short, long)]
-    album_path: String,
+    album_path: Option<String>,
 
     #[arg(short, long, default_value_t = false)]
     no_rename_files: bool,
+
+    /// Show the changes each track would get, without writing or renaming anything
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
 }
 
-fn main() {
-    let args = Cli::parse();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Restore an album's files to how they were before the last run
+    Undo {
+        #[arg(short, long)]
+        album_path: String,
+    },
+    /// Print the JSON Schema of the album config, for editors to complete and check configs with
+    Schema,
+    /// Inspect riptagger's settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the settings a run would use, and where each was set
+    Show {
+        /// Album config file whose settings to include. Looked for in the album directory when not given
+        #[arg(short, long)]
+        config_path: Option<String>,
+
+        /// Directory of the album's audio files
+        #[arg(short, long)]
+        album_path: Option<String>,
 
-    let album_tags: AlbumTags = match load_config_from_file(&args.config_path) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            panic!("Could not read config file '{0}'. Error: '{1}'", &args.config_path, e)
+        /// Format of the config file: toml, yaml or json. Taken from its extension when not given
+        #[arg(long)]
+        config_format: Option<ConfigFormat>,
+    },
+}
+
+/// Load an album config in `format`, or the format its extension names.
+fn load_album_config(config_path: &str, format: Option<ConfigFormat>) -> Result<(AlbumTags, ResolvedConfig), RiptaggerError> {
+    let format = format.or_else(|| ConfigFormat::from_path(config_path)).unwrap_or_default();
+    load_resolved_config(config_path, format).map_err(|e| RiptaggerError::config(config_path, e))
+}
+
+/// Merge the user config's settings, the album config's and `command_line`.
+fn settings(resolved: Option<&ResolvedConfig>, command_line: &SettingsConfig) -> Result<Settings, RiptaggerError> {
+    let user = match user_config_path() {
+        Some(path) => {
+            let path = path.to_string_lossy().to_string();
+            let settings = load_user_settings(Path::new(&path)).map_err(|e| RiptaggerError::config(&path, e))?;
+            Some((path, settings))
         }
+        None => None,
+    };
+    let album = match resolved {
+        Some(resolved) => SettingsConfig::from_album_config(resolved)
+            .map_err(|e| RiptaggerError::config(&resolved.files[0].path, e))?,
+        None => None,
     };
 
-    let mut tracks = match get_audio_files_in_directory(&args.album_path) {
-        Ok(entries) => {
-            entries.iter()
-                .map(|de| String::from(de.path().as_os_str().to_str().unwrap()))
-                .collect::<Vec<String>>()
-        }
-        Err(e) => {
-            panic!("Could not read audio files [{:?}]", e)
-        }
+    let mut layers: Vec<(&str, &SettingsConfig)> = Vec::new();
+    layers.extend(user.iter().map(|(path, settings)| (path.as_str(), settings)));
+    layers.extend(album.iter().map(|(path, settings)| (path.as_str(), settings)));
+    layers.push(("command line", command_line));
+    Ok(Settings::from_layers(&layers))
+}
+
+fn show_config(config_path: Option<&str>, album_path: Option<&str>, format: Option<ConfigFormat>) -> Result<(), RiptaggerError> {
+    // without an album config, show the user config's settings
+    let resolved = match config_and_album_paths(config_path, album_path) {
+        Some((config_path, _)) => Some(load_album_config(&config_path, format)?.1),
+        None => None,
     };
-    tracks.sort();
-    let track_tags = to_track_tags(album_tags);
+    print!("{}", settings(resolved.as_ref(), &SettingsConfig::default())?);
+    Ok(())
+}
 
-    if track_tags.len() != tracks.len() {
-        panic!("Error: Configuration defines {} tracks, but {} tracks found in directory {}\n({:?})", track_tags.len(), tracks.len(), &args.album_path, tracks);
+fn undo_album(album_path: &str) -> Result<(), RiptaggerError> {
+    for path in undo(Path::new(album_path))? {
+        println!("Restored '{}'", path.display());
     }
+    Ok(())
+}
 
-    for i in 0..tracks.len() {
-        let track_path = tracks[i].as_str();
-        let tags = &track_tags[i];
-        match assign_tags_to_track(tags, track_path) {
-            Ok(_) => {
-                println!("Tagged '{}'", track_path);
-            },
-            Err(e) => {
-                println!("Error tagging '{}': {:#?}", track_path, e);
-                continue
-            }
+fn tag_album(args: &Cli) -> Result<(), RiptaggerError> {
+    let (config_path, album_path) = config_and_album_paths(args.config_path.as_deref(), args.album_path.as_deref())
+        .ok_or_else(|| RiptaggerError::NoConfig {
+            directory: args.album_path.clone().unwrap_or_else(|| String::from(".")),
+        })?;
+
+    let (tags, resolved) = load_album_config(&config_path, args.config_format)?;
+    let command_line = SettingsConfig {
+        rename_files: args.no_rename_files.then_some(false),
+        ..SettingsConfig::default()
+    };
+    let settings = settings(Some(&resolved), &command_line)?;
+    if args.dry_run {
+        for (file, keys) in resolved.inherited() {
+            println!("Using {} from '{}'", keys.join(", "), file);
         }
-        if args.no_rename_files {
-            continue;
+    }
+    let album = Album::new(tags, &album_path);
+
+    let plans = album.plan(&PlanOptions::from(&settings))?;
+
+    let registry = TagWriterRegistry::from(&settings);
+    for plan in &plans {
+        if let (true, Some(content), Some(extension)) = (plan.detected.is_mismatched(), plan.detected.content, plan.detected.extension) {
+            println!("Warning: '{}' has a {} extension but contains {} data, tagging it by its contents", plan.path.display(), extension, content);
         }
-        // rename file to 'track number - track name'
-        let track_num = tags.track_number.unwrap_or(0);
-        match rename_audio_file(track_path, track_num, &tags.track_name) {
-            Ok(new_name) => {
-                println!("Renamed '{}' to '{}'", track_path, new_name)
+    }
+
+    let mut failed = Vec::new();
+    if args.dry_run {
+        for plan in &plans {
+            match plan.preview(&registry) {
+                Ok(diff) => println!("{}", diff),
+                Err(e) => {
+                    let e = RiptaggerError::track(plan.path.clone(), ApplyError::Tag(e));
+                    eprintln!("{}", e);
+                    failed.push(e);
+                }
             }
-            Err(e) => {
-                println!("Error renaming file '{0}' ({1})", track_path, e);
+        }
+    } else {
+        let mut journal = Journal::begin(&album.directory)?;
+        let results = apply_with_journal(&plans, &registry, &mut journal);
+        journal.finish()?;
+        for (plan, result) in plans.iter().zip(results) {
+            let track_path = plan.path.display();
+            match result {
+                Ok(_) => {
+                    println!("Tagged '{}'", track_path);
+                    if let Some(new_name) = &plan.new_name {
+                        println!("Renamed '{}' to '{}'", track_path, new_name)
+                    }
+                }
+                Err(e) => {
+                    let e = RiptaggerError::track(plan.path.clone(), e);
+                    eprintln!("{}", e);
+                    failed.push(e);
+                }
             }
         }
     }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(RiptaggerError::TracksFailed {
+            failed,
+            total: plans.len(),
+        })
+    }
+}
+
+fn main() {
+    let args = Cli::parse();
+
+    let result = match &args.command {
+        Some(Command::Undo { album_path }) => undo_album(album_path),
+        Some(Command::Schema) => {
+            println!("{:#}", album_config_schema());
+            Ok(())
+        }
+        Some(Command::Config {
+            command: ConfigCommand::Show { config_path, album_path, config_format },
+        }) => show_config(config_path.as_deref(), album_path.as_deref(), *config_format),
+        None => tag_album(&args),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+        exit(e.exit_code());
+    }
 }
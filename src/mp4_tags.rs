@@ -0,0 +1,153 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use mp4ameta::ident::{FreeformIdentBorrowed, FreeformIdentStatic};
+use mp4ameta::{Data, Img, ReadConfig, Tag};
+
+use crate::tag_writer::{parse_year, CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{TagError, TrackTags, DEFAULT_POSITION_TAG};
+
+/// There's no standard atom for a disc subtitle, so use the freeform item other taggers write.
+const DISC_SUBTITLE: FreeformIdentStatic = FreeformIdentStatic::new_static("com.apple.iTunes", "DISCSUBTITLE");
+
+/// Convert a number for the 16-bit fields of a `trkn` or `disk` item.
+fn item_number(n: i64, name: &str, item: &str) -> Result<u16, TagError> {
+    u16::try_from(n).map_err(|_| TagError::TooLarge(format!("{} {} doesn't fit in an MP4 {} item", name, n, item)))
+}
+
+/// Copy `tags` into the `ilst` items of `tag`.
+pub fn apply_mp4_items(tags: &TrackTags, tag: &mut Tag) -> Result<(), TagError> {
+    if let Some(album_name) = &tags.album_name {
+        tag.set_album(album_name);
+    }
+
+    if let Some(artist_name) = &tags.artist_name {
+        tag.set_artist(artist_name);
+    }
+
+    if let Some(album_artist) = &tags.album_artist {
+        tag.set_album_artist(album_artist);
+    }
+
+    if let Some(composer) = &tags.composer {
+        tag.set_composer(composer);
+    }
+
+    tag.set_title(&tags.track_name);
+
+    // trkn and disk hold the number and total in one item
+    let track_number = tags.track_number.map(|n| item_number(n, "track number", "trkn")).transpose()?;
+    let track_total = tags.track_total.map(|n| item_number(n, "track total", "trkn")).transpose()?;
+    match (track_number, track_total) {
+        (Some(n), Some(total)) => tag.set_track(n, total),
+        (Some(n), None) => tag.set_track_number(n),
+        (None, Some(total)) => tag.set_total_tracks(total),
+        (None, None) => (),
+    }
+
+    let disc_number = tags.disc_number.map(|n| item_number(n, "disc number", "disk")).transpose()?;
+    let disc_total = tags.disc_total.map(|n| item_number(n, "disc total", "disk")).transpose()?;
+    match (disc_number, disc_total) {
+        (Some(n), Some(total)) => tag.set_disc(n, total),
+        (Some(n), None) => tag.set_disc_number(n),
+        (None, Some(total)) => tag.set_total_discs(total),
+        (None, None) => (),
+    }
+
+    if let Some(disc_subtitle) = &tags.disc_subtitle {
+        tag.set_data(DISC_SUBTITLE, Data::Utf8(disc_subtitle.clone()));
+    }
+
+    if let Some(position) = &tags.position {
+        let ident = FreeformIdentBorrowed::new_borrowed("com.apple.iTunes", tags.position_key());
+        tag.set_data(ident, Data::Utf8(position.clone()));
+    }
+
+    if let Some(year) = tags.year {
+        tag.set_year(year.to_string());
+    }
+
+    if !tags.genre.is_empty() {
+        tag.set_genres(tags.genre.clone());
+    }
+
+    match tags.compilation {
+        Some(true) => tag.set_compilation(),
+        Some(false) => tag.remove_compilation(),
+        None => (),
+    }
+
+    if let Some(cover) = CoverPicture::from_tags(tags)? {
+        let image = match cover.mime_type {
+            "image/png" => Img::png(cover.data),
+            _ => Img::jpeg(cover.data),
+        };
+        tag.set_artwork(image);
+    }
+
+    Ok(())
+}
+
+/// Map the `ilst` items of `tag` back onto `TrackTags`.
+pub fn track_tags_from_mp4(tag: &Tag, position_tag: Option<&str>) -> TrackTags {
+    let position = FreeformIdentBorrowed::new_borrowed("com.apple.iTunes", position_tag.unwrap_or(DEFAULT_POSITION_TAG));
+    let (track_number, track_total) = tag.track();
+    let (disc_number, disc_total) = tag.disc();
+    TrackTags {
+        album_name: tag.album().map(String::from),
+        artist_name: tag.artist().map(String::from),
+        album_artist: tag.album_artist().map(String::from),
+        composer: tag.composer().map(String::from),
+        year: tag.year().and_then(parse_year),
+        track_name: tag.title().unwrap_or_default().to_string(),
+        genre: tag.genres().map(String::from).collect(),
+        picture_path: None,
+        track_number: track_number.map(i64::from),
+        track_total: track_total.map(i64::from),
+        disc_number: disc_number.map(i64::from),
+        disc_total: disc_total.map(i64::from),
+        disc_subtitle: tag.strings_of(&DISC_SUBTITLE).next().map(String::from),
+        position: tag.strings_of(&position).next().map(String::from),
+        position_tag: position_tag.map(String::from),
+        // cpil is either set or absent
+        compilation: tag.compilation().then_some(true),
+    }
+}
+
+/// iTunes-style `ilst` items in an MP4/M4A file.
+pub struct Mp4Writer;
+
+struct Mp4File {
+    path: String,
+    tag: Tag,
+}
+
+impl TagWriter for Mp4Writer {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        // audio info isn't needed, and not parsing it avoids failing on unusual codecs
+        let cfg = ReadConfig {
+            read_audio_info: false,
+            ..ReadConfig::DEFAULT
+        };
+        Ok(Box::new(Mp4File {
+            path: track_path.to_string(),
+            tag: Tag::read_with_path(track_path, &cfg)?,
+        }))
+    }
+}
+
+impl TrackFile for Mp4File {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_mp4(&self.tag, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        apply_mp4_items(tags, &mut self.tag)
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        // mp4ameta moves chunk offsets in stco/co64 if moov grows in front of mdat
+        self.tag.write_to_path(&self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::collections::HashMap;
+use std::fs::{read, write};
+use std::io::Cursor;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use metaflac::block::{Picture, PictureType};
+use ogg::{PacketReader, PacketWriteEndInfo, PacketWriter};
+
+use crate::tag_writer::{CoverPicture, TagWriter, TrackFile};
+use crate::track_tags::{picture_mime_type, track_tags_from_vorbis, vorbis_comments, TagError, TrackTags};
+
+const VORBIS_IDENT_MAGIC: &[u8] = b"\x01vorbis";
+const VORBIS_COMMENT_MAGIC: &[u8] = b"\x03vorbis";
+const OPUS_IDENT_MAGIC: &[u8] = b"OpusHead";
+const OPUS_COMMENT_MAGIC: &[u8] = b"OpusTags";
+
+const PICTURE_KEY: &str = "METADATA_BLOCK_PICTURE";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OggCodec {
+    Vorbis,
+    Opus,
+}
+
+impl OggCodec {
+    /// Identify the codec of a logical stream from its first packet.
+    fn from_ident_packet(packet: &[u8]) -> Option<Self> {
+        if packet.starts_with(VORBIS_IDENT_MAGIC) {
+            Some(OggCodec::Vorbis)
+        } else if packet.starts_with(OPUS_IDENT_MAGIC) {
+            Some(OggCodec::Opus)
+        } else {
+            None
+        }
+    }
+
+    fn comment_magic(&self) -> &'static [u8] {
+        match self {
+            OggCodec::Vorbis => VORBIS_COMMENT_MAGIC,
+            OggCodec::Opus => OPUS_COMMENT_MAGIC,
+        }
+    }
+}
+
+/// The comment header packet of a Vorbis or Opus stream.
+#[derive(Debug, Clone)]
+pub struct CommentHeader {
+    pub codec: OggCodec,
+    pub vendor: String,
+    /// Comments in file order. Keys are stored as found in the file.
+    pub comments: Vec<(String, String)>,
+    /// Opus allows binary data after the comment list, it must be kept as-is.
+    trailing: Vec<u8>,
+}
+
+fn read_u32_le(data: &[u8], pos: &mut usize) -> Result<u32, TagError> {
+    let bytes = data
+        .get(*pos..*pos + 4)
+        .ok_or_else(|| TagError::InvalidFile(String::from("truncated comment header")))?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_utf8(data: &[u8], pos: &mut usize, len: usize) -> Result<String, TagError> {
+    let bytes = data
+        .get(*pos..*pos + len)
+        .ok_or_else(|| TagError::InvalidFile(String::from("truncated comment header")))?;
+    *pos += len;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| TagError::InvalidFile(String::from("comment is not valid UTF-8")))
+}
+
+impl CommentHeader {
+    pub fn parse(codec: OggCodec, packet: &[u8]) -> Result<Self, TagError> {
+        let magic = codec.comment_magic();
+        if !packet.starts_with(magic) {
+            return Err(TagError::InvalidFile(String::from(
+                "second packet is not a comment header",
+            )));
+        }
+        let mut pos = magic.len();
+
+        let vendor_len = read_u32_le(packet, &mut pos)? as usize;
+        let vendor = read_utf8(packet, &mut pos, vendor_len)?;
+
+        let count = read_u32_le(packet, &mut pos)?;
+        let mut comments = Vec::new();
+        for _ in 0..count {
+            let len = read_u32_le(packet, &mut pos)? as usize;
+            let comment = read_utf8(packet, &mut pos, len)?;
+            // comments without '=' are invalid, drop them rather than fail
+            if let Some((key, value)) = comment.split_once('=') {
+                comments.push((key.to_string(), value.to_string()));
+            }
+        }
+
+        let trailing = match codec {
+            OggCodec::Opus => packet[pos..].to_vec(),
+            // only the framing bit follows a Vorbis comment header
+            OggCodec::Vorbis => Vec::new(),
+        };
+
+        Ok(CommentHeader {
+            codec,
+            vendor,
+            comments,
+            trailing,
+        })
+    }
+
+    pub fn to_packet(&self) -> Vec<u8> {
+        let mut packet = self.codec.comment_magic().to_vec();
+        packet.extend((self.vendor.len() as u32).to_le_bytes());
+        packet.extend(self.vendor.as_bytes());
+        packet.extend((self.comments.len() as u32).to_le_bytes());
+        for (key, value) in &self.comments {
+            let comment = format!("{}={}", key, value);
+            packet.extend((comment.len() as u32).to_le_bytes());
+            packet.extend(comment.as_bytes());
+        }
+        match self.codec {
+            OggCodec::Vorbis => packet.push(1),
+            OggCodec::Opus => packet.extend(&self.trailing),
+        }
+        packet
+    }
+
+    /// All values for `key`, field names are compared case-insensitively.
+    pub fn get(&self, key: &str) -> Vec<&str> {
+        self.comments
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// Replace all values for `key`.
+    pub fn set(&mut self, key: &str, values: Vec<String>) {
+        self.comments.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+        for value in values {
+            self.comments.push((key.to_string(), value));
+        }
+    }
+
+    /// Replace the front cover, keeping pictures of any other type.
+    pub fn set_front_cover(&mut self, data: Vec<u8>) {
+        self.comments.retain(|(k, v)| {
+            !k.eq_ignore_ascii_case(PICTURE_KEY) || !is_front_cover_block(v)
+        });
+        let mut picture = Picture::new();
+        picture.picture_type = PictureType::CoverFront;
+        picture.mime_type = picture_mime_type(&data).to_string();
+        picture.data = data;
+        self.comments
+            .push((PICTURE_KEY.to_string(), BASE64.encode(picture.to_bytes())));
+    }
+}
+
+fn is_front_cover_block(encoded: &str) -> bool {
+    match BASE64.decode(encoded) {
+        Ok(block) => block.get(0..4) == Some(&(PictureType::CoverFront as u32).to_be_bytes()),
+        // a picture we can't decode is not worth keeping around
+        Err(_) => true,
+    }
+}
+
+fn end_info(packet: &ogg::Packet) -> PacketWriteEndInfo {
+    if packet.last_in_stream() {
+        PacketWriteEndInfo::EndStream
+    } else if packet.last_in_page() {
+        PacketWriteEndInfo::EndPage
+    } else {
+        PacketWriteEndInfo::NormalPacket
+    }
+}
+
+/// Rewrite the comment header of every Vorbis/Opus stream in `data` with `edit`.
+/// All other packets are copied across with their original page boundaries
+/// and granule positions; page sequence numbers and checksums are recomputed.
+fn rewrite_comment_headers<F>(data: Vec<u8>, mut edit: F) -> Result<Vec<u8>, TagError>
+where
+    F: FnMut(&mut CommentHeader),
+{
+    let mut reader = PacketReader::new(Cursor::new(data));
+    let mut writer = PacketWriter::new(Vec::new());
+    // serial -> (codec, packets seen so far)
+    let mut streams: HashMap<u32, (Option<OggCodec>, usize)> = HashMap::new();
+    let mut rewritten = false;
+
+    while let Some(packet) = reader.read_packet()? {
+        let serial = packet.stream_serial();
+        let stream = streams.entry(serial).or_insert((None, 0));
+        if stream.1 == 0 {
+            stream.0 = OggCodec::from_ident_packet(&packet.data);
+        }
+        let index = stream.1;
+        stream.1 += 1;
+
+        let info = end_info(&packet);
+        let absgp = packet.absgp_page();
+        let contents = match (index, stream.0) {
+            (1, Some(codec)) => {
+                let mut header = CommentHeader::parse(codec, &packet.data)?;
+                edit(&mut header);
+                rewritten = true;
+                header.to_packet()
+            }
+            _ => packet.data,
+        };
+        writer.write_packet(contents, serial, info, absgp)?;
+    }
+
+    if !rewritten {
+        return Err(TagError::InvalidFile(String::from(
+            "no Vorbis or Opus stream found",
+        )));
+    }
+    Ok(writer.into_inner())
+}
+
+/// The comment header of the first Vorbis/Opus stream in `data`.
+fn first_comment_header(data: Vec<u8>) -> Result<CommentHeader, TagError> {
+    let mut reader = PacketReader::new(Cursor::new(data));
+    let mut codecs: HashMap<u32, OggCodec> = HashMap::new();
+    while let Some(packet) = reader.read_packet()? {
+        let serial = packet.stream_serial();
+        if packet.first_in_stream() {
+            if let Some(codec) = OggCodec::from_ident_packet(&packet.data) {
+                codecs.insert(serial, codec);
+            }
+        } else if let Some(codec) = codecs.remove(&serial) {
+            return CommentHeader::parse(codec, &packet.data);
+        }
+    }
+    Err(TagError::InvalidFile(String::from(
+        "no Vorbis or Opus stream found",
+    )))
+}
+
+/// Read the comment header of the first Vorbis/Opus stream in a file.
+#[cfg(test)]
+pub fn read_ogg_comments(track_path: &str) -> Result<CommentHeader, TagError> {
+    first_comment_header(read(track_path)?)
+}
+
+/// Vorbis comments in the comment header of every Vorbis or Opus stream in an Ogg file.
+pub struct OggWriter;
+
+struct OggFile {
+    path: String,
+    data: Vec<u8>,
+    /// The first stream's comments, kept up to date for `read`.
+    header: CommentHeader,
+    /// Edits to replay on every stream when saving.
+    fields: Vec<(String, Vec<String>)>,
+    cover: Option<Vec<u8>>,
+}
+
+impl TagWriter for OggWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        let data = read(track_path)?;
+        Ok(Box::new(OggFile {
+            path: track_path.to_string(),
+            header: first_comment_header(data.clone())?,
+            data,
+            fields: Vec::new(),
+            cover: None,
+        }))
+    }
+}
+
+impl TrackFile for OggFile {
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_vorbis(|key| self.header.get(key).into_iter().map(String::from).collect(), position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        let cover = CoverPicture::from_tags(tags)?;
+        for (key, values) in vorbis_comments(tags) {
+            self.header.set(&key, values.clone());
+            self.fields.push((key, values));
+        }
+        if let Some(cover) = cover {
+            self.header.set_front_cover(cover.data.clone());
+            self.cover = Some(cover.data);
+        }
+        Ok(())
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        let OggFile { path, data, fields, cover, .. } = *self;
+        let data = rewrite_comment_headers(data, |header| {
+            for (key, values) in &fields {
+                header.set(key, values.clone());
+            }
+            if let Some(pic) = &cover {
+                header.set_front_cover(pic.clone());
+            }
+        })?;
+
+        write(path, data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{metadata, read, rename};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::album_tags::{to_track_tags, AlbumTags};
+use crate::audio_format::{detect_audio_format, AudioFormat, DetectedFormat};
+use crate::config::{load_config_from_file, load_config_from_file_as, ConfigError, ConfigFormat};
+use crate::diff::{field_changes, TrackDiff};
+use crate::fs_utils::{
+    audio_file_name, file_name_at, find_cover_picture, get_audio_files_in_directory, templated_file_name, temporary_path,
+    track_label,
+};
+use crate::journal::{Journal, JournalError};
+use crate::settings::{GenreCase, Settings};
+use crate::tag_writer::TagWriterRegistry;
+use crate::track_tags::{TagError, TrackTags};
+
+#[derive(Debug)]
+pub enum PlanError {
+    /// The album directory couldn't be listed.
+    Discovery(io::Error),
+    /// A track's contents couldn't be read to detect its format.
+    Detection(PathBuf, io::Error),
+    /// Neither the contents nor the extension of a track match a supported format.
+    UnsupportedFormat(PathBuf),
+    TrackCountMismatch {
+        configured: usize,
+        found: Vec<PathBuf>,
+    },
+    /// The cover picture is bigger than the `max_picture_size` setting.
+    PictureTooLarge {
+        path: String,
+        size: u64,
+        limit: u64,
+    },
+    /// A track would be renamed to `new_path`, the name of a file that isn't one of the album's tracks.
+    NameTaken { path: PathBuf, new_path: PathBuf },
+    /// Two tracks would be renamed to the same `new_path`.
+    DuplicateName {
+        first: PathBuf,
+        second: PathBuf,
+        new_path: PathBuf,
+    },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use PlanError::*;
+        match self {
+            Discovery(e) => write!(f, "Could not read audio files: {}", e),
+            Detection(path, e) => write!(f, "Could not read '{}': {}", path.display(), e),
+            UnsupportedFormat(path) => write!(f, "Unsupported format: {}", path.display()),
+            TrackCountMismatch { configured, found } => write!(
+                f,
+                "Configuration defines {} tracks, but {} tracks found\n({:?})",
+                configured,
+                found.len(),
+                found
+            ),
+            PictureTooLarge { path, size, limit } => write!(
+                f,
+                "Picture '{}' is {} bytes, over the max_picture_size of {} bytes",
+                path, size, limit
+            ),
+            NameTaken { path, new_path } => write!(
+                f,
+                "Can't rename '{}' to '{}', a file that isn't one of the album's tracks has that name",
+                path.display(),
+                new_path.display()
+            ),
+            DuplicateName { first, second, new_path } => write!(
+                f,
+                "'{}' and '{}' would both be renamed to '{}'",
+                first.display(),
+                second.display(),
+                new_path.display()
+            ),
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+#[derive(Debug)]
+pub enum ApplyError {
+    Tag(TagError),
+    /// The track was tagged, but renaming it failed.
+    Rename(io::Error),
+    /// The undo journal couldn't be written, the track was left alone.
+    Journal(JournalError),
+}
+
+impl From<TagError> for ApplyError {
+    fn from(e: TagError) -> Self {
+        ApplyError::Tag(e)
+    }
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ApplyError::*;
+        match self {
+            Tag(e) => write!(f, "Error tagging file: {}", e),
+            Rename(e) => write!(f, "Error renaming file: {}", e),
+            Journal(e) => write!(f, "Error writing undo journal: {}", e),
+        }
+    }
+}
+
+impl Error for ApplyError {}
+
+/// Options controlling how an album is turned into track plans.
+#[derive(Debug, Clone)]
+pub struct PlanOptions {
+    /// Rename files to 'track number - track name' after tagging,
+    /// 'disc-track number - track name' on multi-disc albums, or
+    /// 'side position - track name' on vinyl.
+    pub rename_files: bool,
+    /// Names renamed files from `{name}` placeholders instead, as described for
+    /// `SettingsConfig::file_name_template`.
+    pub file_name_template: Option<String>,
+    pub genre_case: GenreCase,
+    /// Largest cover picture to embed, in bytes.
+    pub max_picture_size: Option<u64>,
+}
+
+impl Default for PlanOptions {
+    fn default() -> Self {
+        PlanOptions::from(&Settings::default())
+    }
+}
+
+impl From<&Settings> for PlanOptions {
+    fn from(settings: &Settings) -> Self {
+        PlanOptions {
+            rename_files: settings.rename_files,
+            file_name_template: settings.file_name_template.clone(),
+            genre_case: settings.genre_case,
+            max_picture_size: settings.max_picture_size,
+        }
+    }
+}
+
+/// An album's tags together with the directory holding its audio files.
+#[derive(Debug, Clone)]
+pub struct Album {
+    pub tags: AlbumTags,
+    pub directory: PathBuf,
+}
+
+impl Album {
+    pub fn new(tags: AlbumTags, directory: impl Into<PathBuf>) -> Self {
+        Album {
+            tags,
+            directory: directory.into(),
+        }
+    }
+
+    pub fn from_config_file(config_path: &str, directory: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        Ok(Album::new(load_config_from_file(config_path)?, directory))
+    }
+
+    /// Like `from_config_file`, for a config in `format` whatever its extension.
+    pub fn from_config_file_as(
+        config_path: &str,
+        format: ConfigFormat,
+        directory: impl Into<PathBuf>,
+    ) -> Result<Self, ConfigError> {
+        Ok(Album::new(load_config_from_file_as(config_path, format)?, directory))
+    }
+
+    /// Match the audio files in the album directory, in file name order,
+    /// to the configured tracks. Nothing on disk is changed. When the config has
+    /// no picture, a `cover`, `folder` or `front` picture in the directory is used.
+    pub fn plan(&self, options: &PlanOptions) -> Result<Vec<TrackPlan>, PlanError> {
+        let mut files: Vec<PathBuf> = get_audio_files_in_directory(&self.directory.to_string_lossy())
+            .map_err(PlanError::Discovery)?
+            .iter()
+            .map(|entry| entry.path())
+            .collect();
+        files.sort();
+
+        let mut tags = self.tags.clone();
+        // without a configured picture, use the cover that came with the rip
+        if tags.picture_path.is_none() {
+            tags.picture_path = find_cover_picture(&self.directory).map(|p| p.to_string_lossy().to_string());
+        }
+        let track_tags = to_track_tags(tags);
+        if track_tags.len() != files.len() {
+            return Err(PlanError::TrackCountMismatch {
+                configured: track_tags.len(),
+                found: files,
+            });
+        }
+
+        let plans = files
+            .into_iter()
+            .zip(track_tags)
+            .map(|(path, tags)| TrackPlan::new(path, tags, options))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut new_paths: HashMap<PathBuf, &PathBuf> = HashMap::new();
+        for plan in &plans {
+            if let Some(first) = new_paths.insert(plan.new_path(), &plan.path) {
+                return Err(PlanError::DuplicateName {
+                    first: first.clone(),
+                    second: plan.path.clone(),
+                    new_path: plan.new_path(),
+                });
+            }
+        }
+        // a track may take another track's name, that one is moved out of the way
+        // first, but never the name of another file
+        for plan in &plans {
+            let new_path = plan.new_path();
+            if new_path != plan.path && new_path.exists() && !plans.iter().any(|p| p.path == new_path) {
+                return Err(PlanError::NameTaken {
+                    path: plan.path.clone(),
+                    new_path,
+                });
+            }
+        }
+        Ok(plans)
+    }
+}
+
+/// The values of a track's `file_name_template` placeholders. `disc_number` is
+/// `None` on single-disc albums, which don't show the disc in file names.
+fn file_name_fields(tags: &TrackTags, disc_number: Option<i64>) -> Vec<(&'static str, String)> {
+    let track = track_label(None, tags.track_number.unwrap_or(0));
+    let number = match &tags.position {
+        Some(position) => position.clone(),
+        None => track_label(disc_number, tags.track_number.unwrap_or(0)),
+    };
+    vec![
+        ("number", number),
+        ("track", track),
+        ("disc", disc_number.map(|d| d.to_string()).unwrap_or_default()),
+        ("position", tags.position.clone().unwrap_or_default()),
+        ("title", tags.track_name.clone()),
+        ("artist", tags.artist_name.clone().unwrap_or_default()),
+        ("album", tags.album_name.clone().unwrap_or_default()),
+        ("year", tags.year.map(|y| y.to_string()).unwrap_or_default()),
+    ]
+}
+
+/// The changes riptagger will make to one file.
+#[derive(Debug, Clone)]
+pub struct TrackPlan {
+    pub path: PathBuf,
+    pub detected: DetectedFormat,
+    /// The format the file will be tagged as.
+    pub format: AudioFormat,
+    pub tags: TrackTags,
+    /// The file name to rename the track to after tagging, if renaming.
+    pub new_name: Option<String>,
+}
+
+impl TrackPlan {
+    pub fn new(path: PathBuf, mut tags: TrackTags, options: &PlanOptions) -> Result<Self, PlanError> {
+        let detected = match detect_audio_format(&path) {
+            Ok(detected) => detected,
+            Err(e) => return Err(PlanError::Detection(path, e)),
+        };
+        let Some(format) = detected.format() else {
+            return Err(PlanError::UnsupportedFormat(path));
+        };
+        if let (Some(limit), Some(picture)) = (options.max_picture_size, &tags.picture_path) {
+            // a picture that can't be read is reported when the track is tagged
+            if let Some(size) = metadata(picture).ok().map(|m| m.len()).filter(|size| *size > limit) {
+                return Err(PlanError::PictureTooLarge {
+                    path: picture.clone(),
+                    size,
+                    limit,
+                });
+            }
+        }
+        tags.genre = tags.genre.iter().map(|genre| options.genre_case.apply(genre)).collect();
+
+        // only multi-disc albums get the disc in the file name
+        let disc_number = tags.disc_number.filter(|_| tags.disc_total.is_some_and(|total| total > 1));
+        let file_path = path.to_string_lossy();
+        let new_name = options.rename_files.then(|| match (&options.file_name_template, &tags.position) {
+            (Some(template), _) => templated_file_name(&file_path, template, &file_name_fields(&tags, disc_number)),
+            (None, Some(position)) => file_name_at(&file_path, position, &tags.track_name),
+            (None, None) => audio_file_name(&file_path, disc_number, tags.track_number.unwrap_or(0), &tags.track_name),
+        });
+        Ok(TrackPlan {
+            path,
+            detected,
+            format,
+            tags,
+            new_name,
+        })
+    }
+
+    /// Where the file ends up once the plan is applied.
+    pub fn new_path(&self) -> PathBuf {
+        match &self.new_name {
+            Some(name) => self.path.parent().unwrap_or(Path::new(".")).join(name),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Work out what `apply` would change. The tags are applied to the file
+    /// in memory only and read back, so fields a format can't store don't show up.
+    pub fn preview(&self, registry: &TagWriterRegistry) -> Result<TrackDiff, TagError> {
+        let mut file = registry.open(self.format, &self.path.to_string_lossy())?;
+        let position_tag = self.tags.position_tag.as_deref();
+        let before = file.read(position_tag);
+        file.apply(&self.tags)?;
+        let after = file.read(position_tag);
+
+        let new_name = self
+            .new_name
+            .clone()
+            .filter(|name| self.path.file_name() != Some(name.as_ref()));
+        Ok(TrackDiff {
+            path: self.path.clone(),
+            changes: field_changes(&before, &after),
+            picture_path: self.tags.picture_path.clone(),
+            new_name,
+        })
+    }
+
+    /// Tag the file, then rename it. Returns the file's final path. A file
+    /// that already has the new name isn't replaced, the rename fails instead.
+    pub fn apply(&self, registry: &TagWriterRegistry) -> Result<PathBuf, ApplyError> {
+        self.tag(registry, None)?;
+        let new_path = self.new_path();
+        if new_path != self.path {
+            move_file(&self.path, &new_path, None)?;
+        }
+        Ok(new_path)
+    }
+
+    /// `apply`, recording the original file in `journal` first so the run can be undone.
+    pub fn apply_with_journal(&self, registry: &TagWriterRegistry, journal: &mut Journal) -> Result<PathBuf, ApplyError> {
+        let index = self.tag(registry, Some(&mut *journal))?;
+        let new_path = self.new_path();
+        if new_path != self.path {
+            move_file(&self.path, &new_path, Some(journal).zip(index))?;
+        }
+        Ok(new_path)
+    }
+
+    /// Tag the file without renaming it, backing it up in `journal` first.
+    /// Returns the index of its journal entry.
+    fn tag(&self, registry: &TagWriterRegistry, journal: Option<&mut Journal>) -> Result<Option<usize>, ApplyError> {
+        let Some(journal) = journal else {
+            registry.assign_as(&self.tags, &self.path.to_string_lossy(), self.format)?;
+            return Ok(None);
+        };
+        let original = read(&self.path).map_err(|e| ApplyError::Journal(e.into()))?;
+        let index = journal
+            .record_original(&self.path, &original)
+            .map_err(ApplyError::Journal)?;
+
+        registry.assign_as(&self.tags, &self.path.to_string_lossy(), self.format)?;
+        let tagged = read(&self.path).map_err(|e| ApplyError::Journal(e.into()))?;
+        journal
+            .record_tagged(index, &original, &tagged)
+            .map_err(ApplyError::Journal)?;
+        Ok(Some(index))
+    }
+}
+
+/// Rename `from` to `to`, refusing to replace a file already at `to`. With a
+/// journal, the new name is noted in the entry at the given index first.
+fn move_file(from: &Path, to: &Path, journal: Option<(&mut Journal, usize)>) -> Result<(), ApplyError> {
+    if to.exists() {
+        let message = format!("'{}' already exists", to.display());
+        return Err(ApplyError::Rename(io::Error::new(io::ErrorKind::AlreadyExists, message)));
+    }
+    let Some((journal, index)) = journal else {
+        return rename(from, to).map_err(ApplyError::Rename);
+    };
+    journal.record_rename(index, to).map_err(ApplyError::Journal)?;
+    rename(from, to).map_err(|e| {
+        // the file is still at `from`, undo finds it there if this fails too
+        let _ = journal.record_rename(index, from);
+        ApplyError::Rename(e)
+    })
+}
+
+/// Apply every plan, carrying on past tracks that fail. Tracks can be renamed
+/// to each other's names, as when a run is repeated with the track order
+/// corrected, so every renamed file is first moved to a temporary name.
+pub fn apply(plans: &[TrackPlan], registry: &TagWriterRegistry) -> Vec<Result<PathBuf, ApplyError>> {
+    apply_all(plans, registry, None)
+}
+
+/// `apply`, recording every change in `journal` so the run can be undone.
+pub fn apply_with_journal(
+    plans: &[TrackPlan],
+    registry: &TagWriterRegistry,
+    journal: &mut Journal,
+) -> Vec<Result<PathBuf, ApplyError>> {
+    apply_all(plans, registry, Some(journal))
+}
+
+fn apply_all(
+    plans: &[TrackPlan],
+    registry: &TagWriterRegistry,
+    mut journal: Option<&mut Journal>,
+) -> Vec<Result<PathBuf, ApplyError>> {
+    let moved: Vec<Result<(PathBuf, Option<usize>), ApplyError>> = plans
+        .iter()
+        .enumerate()
+        .map(|(i, plan)| {
+            let index = plan.tag(registry, journal.as_deref_mut())?;
+            if plan.new_path() == plan.path {
+                return Ok((plan.path.clone(), index));
+            }
+            let temporary = temporary_path(&plan.path, i);
+            move_file(&plan.path, &temporary, journal.as_deref_mut().zip(index))?;
+            Ok((temporary, index))
+        })
+        .collect();
+
+    plans
+        .iter()
+        .zip(moved)
+        .map(|(plan, moved)| {
+            let (current, index) = moved?;
+            let new_path = plan.new_path();
+            if current != new_path
+                && let Err(e) = move_file(&current, &new_path, journal.as_deref_mut().zip(index))
+            {
+                // back to its own name if that's still free, or left at the temporary one
+                let _ = move_file(&current, &plan.path, journal.as_deref_mut().zip(index));
+                return Err(e);
+            }
+            Ok(new_path)
+        })
+        .collect()
+}
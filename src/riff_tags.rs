@@ -0,0 +1,227 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::fs::{read, write};
+use std::io::Cursor;
+
+use id3::{Tag, Version};
+
+use crate::id3_tags::{apply_id3_frames, downgrade_to_id3v23, track_tags_from_id3};
+use crate::tag_writer::{TagWriter, TrackFile};
+use crate::track_tags::{TagError, TrackTags};
+
+/// A chunk inside the RIFF/WAVE container.
+#[derive(Debug, Clone)]
+pub struct RiffChunk {
+    pub id: [u8; 4],
+    pub data: Vec<u8>,
+}
+
+impl RiffChunk {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.id.to_vec();
+        bytes.extend((self.data.len() as u32).to_le_bytes());
+        bytes.extend(&self.data);
+        // chunks are word aligned
+        if self.data.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn is_info_list(&self) -> bool {
+        &self.id == b"LIST" && self.data.starts_with(b"INFO")
+    }
+
+    fn is_id3(&self) -> bool {
+        &self.id == b"id3 " || &self.id == b"ID3 "
+    }
+}
+
+fn invalid(msg: &str) -> TagError {
+    TagError::InvalidFile(format!("RIFF: {}", msg))
+}
+
+/// Split a sequence of chunks, as found in a RIFF body or a LIST chunk.
+fn parse_chunks(data: &[u8]) -> Result<Vec<RiffChunk>, TagError> {
+    let mut chunks = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let id: [u8; 4] = data[pos..pos + 4].try_into().unwrap();
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = data
+            .get(pos + 8..pos + 8 + size)
+            .ok_or_else(|| invalid("chunk extends past end of file"))?;
+        chunks.push(RiffChunk {
+            id,
+            data: body.to_vec(),
+        });
+        pos += 8 + size + size % 2;
+    }
+    Ok(chunks)
+}
+
+/// The chunks of a WAVE file, in file order.
+pub fn read_wav_chunks(file: &[u8]) -> Result<Vec<RiffChunk>, TagError> {
+    if file.len() < 12 || &file[0..4] != b"RIFF" || &file[8..12] != b"WAVE" {
+        return Err(invalid("not a RIFF/WAVE file"));
+    }
+    let size = u32::from_le_bytes(file[4..8].try_into().unwrap()) as usize;
+    // some writers leave the RIFF size wrong, trust the file length over it
+    let end = (size + 8).min(file.len());
+    parse_chunks(&file[12..end])
+}
+
+/// Read the `LIST/INFO` fields of a WAVE file as (id, text) pairs.
+#[cfg(test)]
+pub fn read_wav_info(track_path: &str) -> Result<Vec<([u8; 4], String)>, TagError> {
+    let chunks = read_wav_chunks(&read(track_path)?)?;
+    let mut fields = Vec::new();
+    for list in chunks.iter().filter(|c| c.is_info_list()) {
+        for field in parse_chunks(&list.data[4..])? {
+            let text = String::from_utf8_lossy(&field.data)
+                .trim_end_matches('\0')
+                .to_string();
+            fields.push((field.id, text));
+        }
+    }
+    Ok(fields)
+}
+
+/// Read the ID3 tag embedded in a WAVE file, starting from an empty tag if it has none.
+#[cfg(test)]
+pub fn read_wav_id3(track_path: &str) -> Result<Tag, TagError> {
+    let chunks = read_wav_chunks(&read(track_path)?)?;
+    match chunks.iter().find(|c| c.is_id3()) {
+        Some(chunk) => Ok(Tag::read_from2(Cursor::new(&chunk.data))?),
+        None => Ok(Tag::new()),
+    }
+}
+
+/// INFO fields for `tags`. RIFF INFO has no disc or track total fields.
+fn info_fields(tags: &TrackTags) -> Vec<(&'static [u8; 4], String)> {
+    let mut fields = Vec::new();
+
+    if let Some(album_name) = &tags.album_name {
+        fields.push((b"IPRD", album_name.clone()));
+    }
+
+    if let Some(artist_name) = &tags.artist_name {
+        fields.push((b"IART", artist_name.clone()));
+    }
+
+    fields.push((b"INAM", tags.track_name.clone()));
+
+    if let Some(track_number) = tags.track_number {
+        fields.push((b"ITRK", track_number.to_string()));
+    }
+
+    if let Some(year) = tags.year {
+        fields.push((b"ICRD", year.to_string()));
+    }
+
+    if !tags.genre.is_empty() {
+        fields.push((b"IGNR", tags.genre.join("; ")));
+    }
+
+    fields
+}
+
+/// Build a new `LIST/INFO` chunk, keeping fields riptagger doesn't manage.
+fn info_chunk(existing: Option<&RiffChunk>, tags: &TrackTags) -> Result<RiffChunk, TagError> {
+    let fields = info_fields(tags);
+    let mut subchunks = match existing {
+        Some(list) => parse_chunks(&list.data[4..])?,
+        None => Vec::new(),
+    };
+    subchunks.retain(|c| !fields.iter().any(|(id, _)| **id == c.id));
+    for (id, text) in fields {
+        let mut data = text.into_bytes();
+        data.push(0);
+        subchunks.push(RiffChunk { id: *id, data });
+    }
+
+    let mut data = b"INFO".to_vec();
+    for c in subchunks {
+        data.extend(c.to_bytes());
+    }
+    Ok(RiffChunk { id: *b"LIST", data })
+}
+
+/// A `LIST/INFO` chunk and an `id3 ` chunk in a RIFF/WAVE file.
+pub struct WavWriter {
+    /// The version the ID3 chunk is written as, whatever version the file had.
+    pub version: Version,
+}
+
+impl Default for WavWriter {
+    fn default() -> Self {
+        WavWriter {
+            version: Version::Id3v24,
+        }
+    }
+}
+
+struct WavFile {
+    path: String,
+    chunks: Vec<RiffChunk>,
+    id3_tag: Tag,
+    version: Version,
+}
+
+impl TagWriter for WavWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        let chunks = read_wav_chunks(&read(track_path)?)?;
+        let id3_tag = match chunks.iter().find(|c| c.is_id3()) {
+            Some(chunk) => Tag::read_from2(Cursor::new(&chunk.data))?,
+            None => Tag::new(),
+        };
+        Ok(Box::new(WavFile {
+            path: track_path.to_string(),
+            chunks,
+            id3_tag,
+            version: self.version,
+        }))
+    }
+}
+
+impl TrackFile for WavFile {
+    /// Read from the ID3 chunk, INFO has no fields for totals or disc numbers.
+    fn read(&self, position_tag: Option<&str>) -> TrackTags {
+        track_tags_from_id3(&self.id3_tag, position_tag)
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        let info = info_chunk(self.chunks.iter().find(|c| c.is_info_list()), tags)?;
+        self.chunks.retain(|c| !c.is_info_list());
+        self.chunks.push(info);
+        apply_id3_frames(tags, &mut self.id3_tag)
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        let WavFile { path, mut chunks, mut id3_tag, version } = *self;
+        if version == Version::Id3v23 {
+            downgrade_to_id3v23(&mut id3_tag);
+        }
+        let mut id3_data = Vec::new();
+        id3_tag.write_to(&mut id3_data, version)?;
+
+        // metadata chunks are rewritten after the audio data
+        let info = chunks.iter().position(|c| c.is_info_list()).map(|i| chunks.remove(i));
+        chunks.retain(|c| !c.is_info_list() && !c.is_id3());
+        chunks.extend(info);
+        chunks.push(RiffChunk {
+            id: *b"id3 ",
+            data: id3_data,
+        });
+
+        let body: Vec<u8> = chunks.iter().flat_map(|c| c.to_bytes()).collect();
+        let mut file = b"RIFF".to_vec();
+        file.extend(((body.len() + 4) as u32).to_le_bytes());
+        file.extend(b"WAVE");
+        file.extend(body);
+
+        write(path, file)?;
+        Ok(())
+    }
+}
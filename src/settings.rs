@@ -0,0 +1,236 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+//! Preferences that apply to every run, rather than describing an album.
+//!
+//! Settings are read from layers, each over the one before: the defaults, the
+//! user config at [`user_config_path`], the `[settings]` table of the album
+//! config, then the command line.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::config::{ConfigError, ConfigFormat};
+use crate::config_chain::ResolvedConfig;
+use crate::diagnostic::diagnose;
+use crate::validation::validate_settings_table;
+
+/// How genres are capitalised before they're written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GenreCase {
+    /// Leave genres as the config writes them.
+    #[default]
+    AsWritten,
+    /// Capitalise the first letter of each word, as in "Post-Punk".
+    Title,
+    Lower,
+    Upper,
+}
+
+impl GenreCase {
+    pub fn apply(&self, genre: &str) -> String {
+        match self {
+            GenreCase::AsWritten => genre.to_string(),
+            GenreCase::Lower => genre.to_lowercase(),
+            GenreCase::Upper => genre.to_uppercase(),
+            GenreCase::Title => {
+                let mut start_of_word = true;
+                genre
+                    .chars()
+                    .map(|c| {
+                        let c = if start_of_word { c.to_uppercase().next().unwrap_or(c) } else { c };
+                        start_of_word = c.is_whitespace() || c == '-' || c == '/';
+                        c
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for GenreCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenreCase::AsWritten => write!(f, "as_written"),
+            GenreCase::Title => write!(f, "title"),
+            GenreCase::Lower => write!(f, "lower"),
+            GenreCase::Upper => write!(f, "upper"),
+        }
+    }
+}
+
+/// The ID3v2 version MP3, AIFF and WAV files are tagged with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, JsonSchema)]
+pub enum Id3Version {
+    /// ID3v2.3, for players that don't read ID3v2.4.
+    #[serde(rename = "2.3")]
+    V23,
+    #[default]
+    #[serde(rename = "2.4")]
+    V24,
+}
+
+impl From<Id3Version> for id3::Version {
+    fn from(version: Id3Version) -> Self {
+        match version {
+            Id3Version::V23 => id3::Version::Id3v23,
+            Id3Version::V24 => id3::Version::Id3v24,
+        }
+    }
+}
+
+impl fmt::Display for Id3Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id3Version::V23 => write!(f, "2.3"),
+            Id3Version::V24 => write!(f, "2.4"),
+        }
+    }
+}
+
+/// One layer of settings, as written in the user config or an album's `[settings]` table.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsConfig {
+    /// Rename files after tagging them, true when omitted.
+    pub rename_files: Option<bool>,
+    /// File name for renamed tracks, without the extension. `{number}` is the
+    /// track number as in the default "{number} - {title}", with the disc or
+    /// side when there is one. `{track}`, `{disc}`, `{position}`, `{title}`,
+    /// `{artist}`, `{album}` and `{year}` are also replaced. It must contain
+    /// `{number}`, `{track}` or `{position}`, and no `/`.
+    pub file_name_template: Option<String>,
+    /// How genres are capitalised, as written when omitted.
+    pub genre_case: Option<GenreCase>,
+    /// Largest cover picture to embed, in bytes. No limit when omitted.
+    #[schemars(range(min = 1))]
+    pub max_picture_size: Option<u64>,
+    /// ID3v2 version for MP3, AIFF and WAV files, "2.4" when omitted.
+    pub id3_version: Option<Id3Version>,
+}
+
+impl SettingsConfig {
+    /// The album config's `[settings]` table, with the file it was set in.
+    pub fn from_album_config(resolved: &ResolvedConfig) -> Result<Option<(String, SettingsConfig)>, ConfigError> {
+        let Some(table) = resolved.table.get("settings") else {
+            return Ok(None);
+        };
+        let source = resolved.source_of("settings").map_or(String::new(), |file| file.path.clone());
+        Ok(Some((source, table.clone().try_into()?)))
+    }
+}
+
+/// The settings a run uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub rename_files: bool,
+    pub file_name_template: Option<String>,
+    pub genre_case: GenreCase,
+    pub max_picture_size: Option<u64>,
+    pub id3_version: Id3Version,
+    /// Where each setting that isn't the default was set, by name.
+    pub sources: BTreeMap<&'static str, String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            rename_files: true,
+            file_name_template: None,
+            genre_case: GenreCase::default(),
+            max_picture_size: None,
+            id3_version: Id3Version::default(),
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Merge `layers`, each a source name and its settings, lowest precedence first.
+    pub fn from_layers(layers: &[(&str, &SettingsConfig)]) -> Self {
+        let mut settings = Settings::default();
+        for (source, layer) in layers {
+            if let Some(rename_files) = layer.rename_files {
+                settings.sources.insert("rename_files", source.to_string());
+                settings.rename_files = rename_files;
+            }
+            if let Some(template) = &layer.file_name_template {
+                settings.sources.insert("file_name_template", source.to_string());
+                settings.file_name_template = Some(template.clone());
+            }
+            if let Some(genre_case) = layer.genre_case {
+                settings.sources.insert("genre_case", source.to_string());
+                settings.genre_case = genre_case;
+            }
+            if let Some(max_picture_size) = layer.max_picture_size {
+                settings.sources.insert("max_picture_size", source.to_string());
+                settings.max_picture_size = Some(max_picture_size);
+            }
+            if let Some(id3_version) = layer.id3_version {
+                settings.sources.insert("id3_version", source.to_string());
+                settings.id3_version = id3_version;
+            }
+        }
+        settings
+    }
+}
+
+impl fmt::Display for Settings {
+    /// One `name = value` line per setting, commented with where it was set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = [
+            ("rename_files", Some(Value::Boolean(self.rename_files))),
+            ("file_name_template", self.file_name_template.clone().map(Value::String)),
+            ("genre_case", Some(Value::String(self.genre_case.to_string()))),
+            ("max_picture_size", self.max_picture_size.map(|n| Value::Integer(n as i64))),
+            ("id3_version", Some(Value::String(self.id3_version.to_string()))),
+        ];
+        for (name, value) in values {
+            let source = self.sources.get(name).map_or("default", String::as_str);
+            match value {
+                Some(value) => writeln!(f, "{} = {}  # {}", name, value, source)?,
+                None => writeln!(f, "# {} is not set", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `$XDG_CONFIG_HOME/riptagger/config.toml`, or `~/.config/riptagger/config.toml`
+/// when `XDG_CONFIG_HOME` isn't set.
+pub fn user_config_path() -> Option<PathBuf> {
+    // the XDG spec says to ignore relative paths
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("riptagger").join("config.toml"))
+}
+
+/// The settings in the user config at `path`, none when there's no file there.
+pub fn load_user_settings(path: &Path) -> Result<SettingsConfig, ConfigError> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(SettingsConfig::default()),
+        Err(e) => return Err(e.into()),
+    };
+    let table = source.parse::<Table>()?;
+    validate_settings_table(&table).map_err(|error| {
+        let path = path.to_string_lossy();
+        ConfigError::InFile {
+            path: path.to_string(),
+            diagnostics: diagnose(&path, &source, ConfigFormat::Toml, &error),
+            error: Box::new(error),
+        }
+    })?;
+    Ok(Value::Table(table).try_into()?)
+}
@@ -0,0 +1,163 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::collections::HashMap;
+use std::fs::read;
+use std::path::Path;
+
+use crate::ape_tags::ApeWriter;
+use crate::asf_tags::AsfWriter;
+use crate::audio_format::{detect_audio_format, AudioFormat};
+use crate::flac_tags::FlacWriter;
+use crate::id3_tags::Id3Writer;
+use crate::mp4_tags::Mp4Writer;
+use crate::ogg_tags::OggWriter;
+use crate::riff_tags::WavWriter;
+use crate::settings::Settings;
+use crate::track_tags::{picture_mime_type, TagError, TrackTags};
+
+/// A tag backend for one container format.
+pub trait TagWriter {
+    /// Load the tags of `track_path`, ready to be read, changed and saved.
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError>;
+}
+
+/// The tags of one open file. Nothing is written until `save`.
+pub trait TrackFile {
+    /// The tags currently held, as far as the backend maps them onto `TrackTags`.
+    /// Embedded pictures are not read back, `picture_path` is always `None`.
+    /// `position` is read from the field `position_tag` names, as when it's written.
+    fn read(&self, position_tag: Option<&str>) -> TrackTags;
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError>;
+
+    fn save(self: Box<Self>) -> Result<(), TagError>;
+}
+
+/// Tag backends keyed by the format they handle.
+pub struct TagWriterRegistry {
+    writers: HashMap<AudioFormat, Box<dyn TagWriter>>,
+}
+
+impl TagWriterRegistry {
+    /// A registry with no backends.
+    pub fn new() -> Self {
+        TagWriterRegistry {
+            writers: HashMap::new(),
+        }
+    }
+
+    /// Handle `format` with `writer`, replacing any backend already registered for it.
+    pub fn register<W: TagWriter + 'static>(&mut self, format: AudioFormat, writer: W) {
+        self.writers.insert(format, Box::new(writer));
+    }
+
+    pub fn get(&self, format: AudioFormat) -> Option<&dyn TagWriter> {
+        self.writers.get(&format).map(|w| w.as_ref())
+    }
+
+    pub fn open(&self, format: AudioFormat, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        match self.get(format) {
+            Some(writer) => writer.open(track_path),
+            None => Err(TagError::UnsupportedFormat(format.to_string())),
+        }
+    }
+
+    /// Tag a track as `format`, regardless of its contents or extension.
+    pub fn assign_as(&self, tags: &TrackTags, track_path: &str, format: AudioFormat) -> Result<(), TagError> {
+        let mut file = self.open(format, track_path)?;
+        file.apply(tags)?;
+        file.save()
+    }
+
+    /// Tag a track, routing it to a backend by its contents rather than its extension.
+    pub fn assign(&self, tags: &TrackTags, track_path: &str) -> Result<(), TagError> {
+        match detect_audio_format(Path::new(track_path))?.format() {
+            Some(format) => self.assign_as(tags, track_path, format),
+            None => Err(TagError::UnsupportedFormat(track_path.to_string())),
+        }
+    }
+}
+
+impl Default for TagWriterRegistry {
+    /// A registry with every built-in backend.
+    fn default() -> Self {
+        let mut registry = TagWriterRegistry::new();
+        registry.register(AudioFormat::Flac, FlacWriter);
+        registry.register(AudioFormat::Mp3, Id3Writer::default());
+        registry.register(AudioFormat::Ogg, OggWriter);
+        registry.register(AudioFormat::Asf, AsfWriter);
+        registry.register(AudioFormat::Mp4, Mp4Writer);
+        registry.register(AudioFormat::Ape, ApeWriter);
+        registry.register(AudioFormat::Wav, WavWriter::default());
+        // AIFF has no INFO list, the id3 crate writes an ID3 chunk into the FORM container
+        registry.register(AudioFormat::Aiff, Id3Writer::default());
+        registry
+    }
+}
+
+impl From<&Settings> for TagWriterRegistry {
+    /// Every built-in backend, set up as `settings` ask.
+    fn from(settings: &Settings) -> Self {
+        let mut registry = TagWriterRegistry::default();
+        let version = settings.id3_version.into();
+        registry.register(AudioFormat::Mp3, Id3Writer { version });
+        registry.register(AudioFormat::Aiff, Id3Writer { version });
+        registry.register(AudioFormat::Wav, WavWriter { version });
+        registry
+    }
+}
+
+/// A front cover image loaded from `TrackTags::picture_path`.
+pub struct CoverPicture {
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+}
+
+impl CoverPicture {
+    pub fn from_tags(tags: &TrackTags) -> Result<Option<Self>, TagError> {
+        match &tags.picture_path {
+            Some(cover_path) => {
+                let data = read(cover_path).map_err(|e| TagError::Picture(cover_path.clone(), e))?;
+                let mime_type = picture_mime_type(&data);
+                Ok(Some(CoverPicture { data, mime_type }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self.mime_type {
+            "image/png" => "png",
+            "image/gif" => "gif",
+            _ => "jpg",
+        }
+    }
+}
+
+/// "n/m" when the total is known, otherwise just "n".
+pub fn format_number_of(number: Option<i64>, total: Option<i64>) -> Option<String> {
+    match (number, total) {
+        (Some(n), Some(t)) => Some(format!("{}/{}", n, t)),
+        (Some(n), None) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Split an "n/m" or "n" value into its number and total.
+pub fn parse_number_of(value: &str) -> (Option<i64>, Option<i64>) {
+    match value.split_once('/') {
+        Some((n, t)) => (n.trim().parse().ok(), t.trim().parse().ok()),
+        None => (value.trim().parse().ok(), None),
+    }
+}
+
+/// A "1"/"0" style flag, as used for compilations.
+pub fn parse_flag(value: &str) -> bool {
+    !matches!(value.trim(), "" | "0")
+}
+
+/// The year at the start of a date such as "2024" or "2024-03-01".
+pub fn parse_year(value: &str) -> Option<i64> {
+    value.trim().get(0..4).and_then(|y| y.parse().ok())
+}
@@ -0,0 +1,108 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::error::Error;
+
+use serde_json::Value;
+use toml::Table;
+
+use crate::album_config::{album_config_schema, AlbumConfig, OneOrMany, TrackConfig};
+use crate::album_tags::{AlbumTags, TrackNumbering};
+use crate::config::ConfigError;
+use crate::validation::validate_album_table;
+
+#[test]
+fn test_schema_keys_pass_validation() -> Result<(), Box<dyn Error>> {
+    // every key the schema documents passes validation, down to track tables
+    let table: Table = toml::from_str(r#"
+        "$schema" = "riptagger.schema.json"
+        version = 1
+        extends = "defaults.toml"
+        include = ["post_punk.toml"]
+        album = "Romance"
+        artist = "Fontaines D.C"
+        album_artist = "Fontaines D.C."
+        composer = "Grian Chatten"
+        compilation = false
+        year = 2024
+        genre = "Indie"
+        picture = "cover.png"
+        sides_per_disc = 2
+        position_tag = "VINYLTRACK"
+        track_numbering = "per_disc"
+
+        [settings]
+        rename_files = true
+        file_name_template = "{number} - {title}"
+        genre_case = "title"
+        max_picture_size = 1000000
+        id3_version = "2.3"
+
+        [[side]]
+        name = "A"
+
+        [[side.track]]
+        title = "Romance"
+        artist = "Fontaines D.C"
+        composer = "Grian Chatten"
+        year = 2024
+        genre = ["Indie", "Post-Punk"]
+    "#)?;
+    validate_album_table(&table)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_track_title_required() {
+    let schema = album_config_schema();
+    assert_eq!(schema["definitions"]["TrackConfig"]["required"], serde_json::json!(["title"]));
+    assert_eq!(schema["additionalProperties"], Value::Bool(false));
+}
+
+#[test]
+fn test_from_config() -> Result<(), Box<dyn Error>> {
+    let config = AlbumConfig {
+        album: Some("Romance".to_string()),
+        artist: Some("Fontaines D.C".to_string()),
+        genre: Some(OneOrMany::One("Indie".to_string())),
+        track: Some(vec![
+            TrackConfig { title: Some("Romance".to_string()), ..TrackConfig::default() },
+            TrackConfig {
+                title: Some("Starburster".to_string()),
+                genre: Some(OneOrMany::Many(vec!["Indie".to_string(), "Post-Punk".to_string()])),
+                ..TrackConfig::default()
+            },
+        ]),
+        track_numbering: Some(TrackNumbering::PerDisc),
+        ..AlbumConfig::default()
+    };
+    let album_tags = AlbumTags::from_config(config)?;
+
+    assert_eq!(album_tags.tracks, vec!["Romance".to_string(), "Starburster".to_string()]);
+    assert_eq!(album_tags.album_artist, Some("Fontaines D.C".to_string()));
+    assert_eq!(album_tags.genre, vec!["Indie".to_string()]);
+    assert_eq!(album_tags.track_overrides[1].genre, Some(vec!["Indie".to_string(), "Post-Punk".to_string()]));
+    assert_eq!(album_tags.track_numbering, TrackNumbering::PerDisc);
+
+    Ok(())
+}
+
+#[test]
+fn test_version() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        version = 1
+        tracks = ["Romance"]
+    "#)?;
+    AlbumTags::from_toml(table)?;
+
+    let table: Table = toml::from_str(r#"
+        version = 2
+        tracks = ["Romance"]
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(error @ ConfigError::TypeError { .. }) => assert_eq!(error.key_path(), Some("version".to_string())),
+        other => panic!("expected an unsupported version, got {:?}", other),
+    }
+
+    Ok(())
+}
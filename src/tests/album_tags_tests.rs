@@ -1,7 +1,7 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 use std::error::Error;
-use toml::Table;
+use toml::{Table, Value};
 
 use crate::config::ConfigError;
 use crate::album_tags::{to_track_tags, AlbumTags};
@@ -143,3 +143,337 @@ fn test_to_track_tags_no_disc_info() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_track_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        album = "Split"
+        artist = "Various"
+        composer = "Unknown"
+        year = 2019
+        genre = "Noise"
+
+        [[track]]
+        title = "Opener"
+
+        [[track]]
+        title = "Guest Spot"
+        artist = "Someone Else"
+        composer = "Someone Else"
+        year = 2018
+        genre = ["Drone", "Ambient"]
+    "#)?;
+    let album_tags = AlbumTags::from_toml(table)?;
+    assert_eq!(album_tags.tracks, vec!["Opener".to_string(), "Guest Spot".to_string()]);
+    assert_eq!(album_tags.track_overrides.len(), 2);
+
+    let track_tags = to_track_tags(album_tags);
+    assert_eq!(track_tags.len(), 2);
+    assert_eq!(track_tags[0].track_name, "Opener");
+    assert_eq!(track_tags[0].artist_name, Some("Various".to_string()));
+    assert_eq!(track_tags[0].composer, Some("Unknown".to_string()));
+    assert_eq!(track_tags[0].year, Some(2019));
+    assert_eq!(track_tags[0].genre, vec!["Noise".to_string()]);
+
+    assert_eq!(track_tags[1].track_name, "Guest Spot");
+    assert_eq!(track_tags[1].artist_name, Some("Someone Else".to_string()));
+    assert_eq!(track_tags[1].composer, Some("Someone Else".to_string()));
+    assert_eq!(track_tags[1].year, Some(2018));
+    assert_eq!(track_tags[1].genre, vec!["Drone".to_string(), "Ambient".to_string()]);
+    // album-wide values are not overridable per track
+    assert_eq!(track_tags[1].album_name, Some("Split".to_string()));
+    assert_eq!(track_tags[1].track_number, Some(2));
+    assert_eq!(track_tags[1].track_total, Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn test_track_table_missing_title() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        [[track]]
+        title = "First"
+
+        [[track]]
+        artist = "No Title"
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(ConfigError::MissingKey(key)) => assert_eq!(key, "track[1].title"),
+        other => panic!("expected a missing title, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tracks_and_track_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["First"]
+
+        [[track]]
+        title = "First"
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(error @ ConfigError::TypeError { .. }) => {
+            assert_eq!(error.key_path(), Some("track".to_string()));
+            assert_eq!(format!("{}", error), "Type error: use either 'tracks' or [[track]] tables, not both");
+        }
+        other => panic!("expected a type error, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_album_artist_defaults_to_artist() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        artist = "Show Me a Dinosaur"
+        tracks = ["Odyssey"]
+    "#)?;
+    let album_tags = AlbumTags::from_toml(table)?;
+    assert_eq!(album_tags.album_artist, Some("Show Me a Dinosaur".to_string()));
+    assert_eq!(album_tags.compilation, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_compilation() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        album = "Split"
+        album_artist = "Various Artists"
+        compilation = true
+
+        [[track]]
+        title = "Opener"
+        artist = "First Band"
+
+        [[track]]
+        title = "Closer"
+        artist = "Second Band"
+    "#)?;
+    let track_tags = to_track_tags(AlbumTags::from_toml(table)?);
+    assert_eq!(track_tags[0].artist_name, Some("First Band".to_string()));
+    assert_eq!(track_tags[1].artist_name, Some("Second Band".to_string()));
+    for tags in &track_tags {
+        assert_eq!(tags.album_artist, Some("Various Artists".to_string()));
+        assert_eq!(tags.compilation, Some(true));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_disc_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        album = "Live at the Hall"
+        artist = "Show Me a Dinosaur"
+        picture = "cover.png"
+
+        [[disc]]
+        subtitle = "Early Show"
+        tracks = ["Intro", "Odyssey"]
+
+        [[disc]]
+        subtitle = "Late Show"
+        picture = "late.png"
+
+        [[disc.track]]
+        title = "Encore"
+        artist = "Special Guest"
+    "#)?;
+    let album_tags = AlbumTags::from_toml(table)?;
+    assert_eq!(album_tags.discs.len(), 2);
+    assert_eq!(album_tags.discs[0].track_count, 2);
+    assert_eq!(album_tags.discs[1].track_count, 1);
+    assert_eq!(album_tags.track_overrides.len(), 3);
+
+    let track_tags = to_track_tags(album_tags);
+    let titles: Vec<&str> = track_tags.iter().map(|t| t.track_name.as_str()).collect();
+    assert_eq!(titles, vec!["Intro", "Odyssey", "Encore"]);
+    let discs: Vec<Option<i64>> = track_tags.iter().map(|t| t.disc_number).collect();
+    assert_eq!(discs, vec![Some(1), Some(1), Some(2)]);
+    assert!(track_tags.iter().all(|t| t.disc_total == Some(2)));
+
+    assert_eq!(track_tags[1].disc_subtitle, Some("Early Show".to_string()));
+    assert_eq!(track_tags[2].disc_subtitle, Some("Late Show".to_string()));
+    assert!(track_tags[1].picture_path.as_ref().unwrap().ends_with("cover.png"));
+    assert!(track_tags[2].picture_path.as_ref().unwrap().ends_with("late.png"));
+    assert_eq!(track_tags[2].artist_name, Some("Special Guest".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_disc_table_missing_tracks() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        [[disc]]
+        tracks = ["One"]
+
+        [[disc]]
+        subtitle = "Nothing Here"
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(ConfigError::MissingKey(key)) => assert_eq!(key, "disc[1].tracks"),
+        other => panic!("expected missing tracks, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_disc_tables_with_flat_layout() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        disc_total = 2
+
+        [[disc]]
+        tracks = ["One"]
+    "#)?;
+    assert!(matches!(AlbumTags::from_toml(table), Err(ConfigError::TypeError { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn test_per_disc_track_numbering() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        track_numbering = "per_disc"
+
+        [[disc]]
+        tracks = ["One", "Two", "Three"]
+
+        [[disc]]
+        tracks = ["Four", "Five"]
+    "#)?;
+    let track_tags = to_track_tags(AlbumTags::from_toml(table)?);
+    let numbers: Vec<(Option<i64>, Option<i64>, Option<i64>)> = track_tags
+        .iter()
+        .map(|t| (t.disc_number, t.track_number, t.track_total))
+        .collect();
+    assert_eq!(numbers, vec![
+        (Some(1), Some(1), Some(3)),
+        (Some(1), Some(2), Some(3)),
+        (Some(1), Some(3), Some(3)),
+        (Some(2), Some(1), Some(2)),
+        (Some(2), Some(2), Some(2)),
+    ]);
+
+    Ok(())
+}
+
+#[test]
+fn test_per_disc_track_numbering_tracks_per_disc() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["One", "Two", "Three"]
+        disc_total = 2
+        tracks_per_disc = [1, 2]
+        track_numbering = "per_disc"
+    "#)?;
+    let track_tags = to_track_tags(AlbumTags::from_toml(table)?);
+    let numbers: Vec<(Option<i64>, Option<i64>)> = track_tags.iter().map(|t| (t.track_number, t.track_total)).collect();
+    assert_eq!(numbers, vec![(Some(1), Some(1)), (Some(1), Some(2)), (Some(2), Some(2))]);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_track_numbering() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["One"]
+        track_numbering = "sideways"
+    "#)?;
+    assert!(matches!(AlbumTags::from_toml(table), Err(ConfigError::TypeError { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn test_side_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        album = "Double LP"
+        position_tag = "DISCOGS_POSITION"
+
+        [[side]]
+        tracks = ["One", "Two"]
+
+        [[side]]
+        tracks = ["Three"]
+
+        [[side]]
+        name = "X"
+        tracks = ["Four"]
+    "#)?;
+    let album_tags = AlbumTags::from_toml(table)?;
+    assert_eq!(album_tags.sides.len(), 3);
+    // two sides to a record
+    assert_eq!(album_tags.discs.len(), 2);
+
+    let track_tags = to_track_tags(album_tags);
+    let positions: Vec<Option<String>> = track_tags.iter().map(|t| t.position.clone()).collect();
+    assert_eq!(positions, vec![
+        Some("A1".to_string()),
+        Some("A2".to_string()),
+        Some("B1".to_string()),
+        Some("X1".to_string()),
+    ]);
+    let numbers: Vec<(Option<i64>, Option<i64>)> = track_tags.iter().map(|t| (t.disc_number, t.track_number)).collect();
+    assert_eq!(numbers, vec![(Some(1), Some(1)), (Some(1), Some(2)), (Some(1), Some(3)), (Some(2), Some(4))]);
+    assert!(track_tags.iter().all(|t| t.position_key() == "DISCOGS_POSITION"));
+
+    Ok(())
+}
+
+#[test]
+fn test_side_tables_per_disc_numbering() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        sides_per_disc = 1
+        track_numbering = "per_disc"
+
+        [[side]]
+        tracks = ["One", "Two"]
+
+        [[side]]
+        tracks = ["Three"]
+    "#)?;
+    let track_tags = to_track_tags(AlbumTags::from_toml(table)?);
+    let numbers: Vec<(Option<i64>, Option<i64>, Option<i64>)> = track_tags
+        .iter()
+        .map(|t| (t.disc_number, t.track_number, t.track_total))
+        .collect();
+    assert_eq!(numbers, vec![(Some(1), Some(1), Some(2)), (Some(1), Some(2), Some(2)), (Some(2), Some(1), Some(1))]);
+    assert_eq!(track_tags[2].position, Some("B1".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_side_and_disc_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        [[disc]]
+        tracks = ["One"]
+
+        [[side]]
+        tracks = ["One"]
+    "#)?;
+    assert!(matches!(AlbumTags::from_toml(table), Err(ConfigError::TypeError { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn test_side_names_past_z() -> Result<(), Box<dyn Error>> {
+    let mut table = Table::new();
+    let sides: Vec<Value> = (0..28)
+        .map(|i| {
+            let mut side = Table::new();
+            side.insert("tracks".to_string(), Value::Array(vec![Value::String(format!("Track {}", i))]));
+            Value::Table(side)
+        })
+        .collect();
+    table.insert("side".to_string(), Value::Array(sides));
+
+    let names: Vec<String> = AlbumTags::from_toml(table)?.sides.into_iter().map(|s| s.name).collect();
+    assert_eq!(names[0], "A");
+    assert_eq!(names[25], "Z");
+    assert_eq!(names[26], "AA");
+    assert_eq!(names[27], "AB");
+
+    Ok(())
+}
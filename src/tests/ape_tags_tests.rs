@@ -0,0 +1,71 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all};
+use std::path::PathBuf;
+
+use crate::ape_tags::{read_ape_tag, FRONT_COVER_KEY};
+use crate::audio_format::AudioFormat;
+use crate::tests::{read_back, write_temp};
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags { disc_total: None, ..crate::tests::sample_tags() }
+}
+
+/// A WavPack block header followed by filler audio data.
+pub fn wavpack_file() -> Vec<u8> {
+    let mut file = b"wvpk".to_vec();
+    file.extend(248u32.to_le_bytes());
+    file.extend([0x42u8; 248]);
+    file
+}
+
+fn write_wavpack(name: &str) -> PathBuf {
+    write_temp(name, "track.wv", &wavpack_file())
+}
+
+fn text(tag: &ape::Tag, key: &str) -> Vec<String> {
+    let values: Vec<&str> = tag.item(key).unwrap().try_into().unwrap();
+    values.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_assign_ape_tags() {
+    let path = write_wavpack("assign_ape_tags");
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let tag = read_ape_tag(&path_str).unwrap();
+    assert_eq!(text(&tag, "Title"), vec!["Starburster"]);
+    assert_eq!(text(&tag, "Album"), vec!["Romance"]);
+    assert_eq!(text(&tag, "Artist"), vec!["Fontaines D.C"]);
+    assert_eq!(text(&tag, "Track"), vec!["2/11"]);
+    assert_eq!(text(&tag, "Disc"), vec!["1"]);
+    assert_eq!(text(&tag, "Year"), vec!["2024"]);
+    assert_eq!(text(&tag, "Genre"), vec!["Indie", "Post-Punk"]);
+
+    let covers = tag.items(FRONT_COVER_KEY);
+    assert_eq!(covers.len(), 1);
+    let cover: Vec<u8> = covers[0].into();
+    let mut expected = b"cover.png\0".to_vec();
+    expected.extend(read("test_data/pictures/cover.png").unwrap());
+    assert_eq!(cover, expected);
+
+    // the tag is appended, audio data stays at the start of the file
+    assert!(read(&path).unwrap()[8..256].iter().all(|&b| b == 0x42));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_ape_tags() {
+    let path = write_wavpack("read_back_ape_tags");
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    let expected = TrackTags { picture_path: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Ape, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
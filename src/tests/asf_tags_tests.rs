@@ -0,0 +1,182 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all, write};
+use std::path::PathBuf;
+
+use crate::asf_tags::read_asf_tags;
+use crate::audio_format::AudioFormat;
+use crate::tests::{read_back, sample_tags, write_temp};
+use crate::track_tags::{assign_tags_to_track, TagError, TrackTags};
+
+const HEADER_GUID: [u8; 16] = [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C];
+const FILE_PROPERTIES_GUID: [u8; 16] = [0xA1, 0xDC, 0xAB, 0x8C, 0x47, 0xA9, 0xCF, 0x11, 0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const DATA_GUID: [u8; 16] = [0x36, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C];
+const HEADER_EXTENSION_GUID: [u8; 16] = [0xB5, 0x03, 0xBF, 0x5F, 0x2E, 0xA9, 0xCF, 0x11, 0x8E, 0xE3, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const HEADER_EXTENSION_RESERVED_GUID: [u8; 16] = [0x11, 0xD2, 0xD3, 0xAB, 0xBA, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const METADATA_LIBRARY_GUID: [u8; 16] = [0x94, 0x1C, 0x23, 0x44, 0x98, 0x94, 0xD1, 0x49, 0xA1, 0x41, 0x1D, 0x13, 0x4E, 0x45, 0x70, 0x54];
+
+
+fn object(guid: [u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut bytes = guid.to_vec();
+    bytes.extend(((data.len() + 24) as u64).to_le_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+/// A header holding a File Properties Object and `objects`, followed by a dummy Data Object.
+pub fn asf_file(objects: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut children = object(FILE_PROPERTIES_GUID, &[0u8; 80]);
+    let count = objects.len() as u32 + 1;
+    children.extend(objects.concat());
+    let data = object(DATA_GUID, &[0x5Au8; 500]);
+    let mut file = HEADER_GUID.to_vec();
+    file.extend(((children.len() + 30) as u64).to_le_bytes());
+    file.extend(count.to_le_bytes());
+    file.extend([1, 2]);
+    file.extend(children);
+    file.extend(data);
+    file
+}
+
+fn write_asf(name: &str) -> PathBuf {
+    write_temp(name, "track.wma", &asf_file(Vec::new()))
+}
+
+/// A Header Extension Object holding a Metadata Library Object with an
+/// `IsVBR` record on stream 1, as encoders write.
+fn stream_record_extension() -> Vec<u8> {
+    let name: Vec<u8> = "IsVBR\0".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+    let mut library = 1u16.to_le_bytes().to_vec();
+    library.extend(0u16.to_le_bytes());
+    library.extend(1u16.to_le_bytes());
+    library.extend((name.len() as u16).to_le_bytes());
+    // BOOL, two bytes in the Metadata Library
+    library.extend(2u16.to_le_bytes());
+    library.extend(2u32.to_le_bytes());
+    library.extend(name);
+    library.extend([1, 0]);
+
+    let library = object(METADATA_LIBRARY_GUID, &library);
+    let mut extension = HEADER_EXTENSION_RESERVED_GUID.to_vec();
+    extension.extend(6u16.to_le_bytes());
+    extension.extend((library.len() as u32).to_le_bytes());
+    extension.extend(library);
+    object(HEADER_EXTENSION_GUID, &extension)
+}
+
+/// The record count of the file's Metadata Library Object.
+fn library_record_count(file: &[u8]) -> u16 {
+    let start = file.windows(16).position(|w| w == METADATA_LIBRARY_GUID).unwrap() + 24;
+    u16::from_le_bytes([file[start], file[start + 1]])
+}
+
+fn file_size_field(file: &[u8]) -> u64 {
+    // header object (30) + file properties object header (24) + file id (16)
+    u64::from_le_bytes(file[70..78].try_into().unwrap())
+}
+
+#[test]
+fn test_assign_asf_tags() {
+    let path = write_asf("assign_asf_tags");
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let tags = read_asf_tags(&path_str).unwrap();
+    assert_eq!(tags.title(), "Starburster");
+    assert_eq!(tags.author(), "Fontaines D.C");
+    let value = |name: &str| tags.get(name).iter().filter_map(|a| a.as_string()).collect::<Vec<String>>();
+    assert_eq!(value("WM/AlbumTitle"), vec!["Romance"]);
+    assert_eq!(value("WM/TrackNumber"), vec!["2"]);
+    assert_eq!(value("WM/PartOfSet"), vec!["1/2"]);
+    assert_eq!(value("WM/Year"), vec!["2024"]);
+    assert_eq!(value("WM/Genre"), vec!["Indie", "Post-Punk"]);
+    assert_eq!(tags.get("WM/Picture").len(), 1);
+    assert_eq!(tags.get("WM/Picture")[0].value[0], 3);
+
+    let file = read(&path).unwrap();
+    assert_eq!(file_size_field(&file), file.len() as u64);
+    assert!(file.ends_with(&[0x5Au8; 500]));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_asf_tags_large_picture() {
+    let path = write_asf("assign_asf_tags_large_picture");
+    let path_str = path.to_string_lossy().to_string();
+
+    let picture_path = path.parent().unwrap().join("cover.png");
+    let mut picture = read("test_data/pictures/cover.png").unwrap();
+    picture.resize(100_000, 0);
+    write(&picture_path, picture).unwrap();
+
+    let mut tags = sample_tags();
+    tags.picture_path = Some(picture_path.to_string_lossy().to_string());
+    assign_tags_to_track(&tags, &path_str).unwrap();
+    assign_tags_to_track(&tags, &path_str).unwrap();
+
+    let asf = read_asf_tags(&path_str).unwrap();
+    assert!(asf.extended.iter().all(|a| a.name != "WM/Picture"));
+    assert_eq!(asf.library.len(), 1);
+    assert_eq!(asf.library[0].name, "WM/Picture");
+    assert_eq!(asf.title(), "Starburster");
+
+    let file = read(&path).unwrap();
+    assert_eq!(file_size_field(&file), file.len() as u64);
+    assert!(file.len() > 100_000);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_asf_tags_title_too_long() {
+    let path = write_asf("assign_asf_tags_title_too_long");
+    let original = read(&path).unwrap();
+    // 40000 UTF-16 characters are more than the 16-bit title length can hold
+    let tags = TrackTags { track_name: "a".repeat(40_000), ..sample_tags() };
+
+    let result = assign_tags_to_track(&tags, &path.to_string_lossy());
+    assert!(matches!(result, Err(TagError::TooLarge(_))));
+    assert_eq!(read(&path).unwrap(), original);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_asf_tags_keeps_stream_records() {
+    let path = write_temp("assign_asf_tags_keeps_stream_records", "track.wma", &asf_file(vec![stream_record_extension()]));
+    let path_str = path.to_string_lossy().to_string();
+    assert_eq!(library_record_count(&read(&path).unwrap()), 1);
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let file = read(&path).unwrap();
+    assert_eq!(library_record_count(&file), 1);
+    assert!(read_asf_tags(&path_str).unwrap().library.is_empty());
+    assert_eq!(file_size_field(&file), file.len() as u64);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_asf_tags_not_asf() {
+    let path = crate::tests::copy_to_temp("test_data/empty_audio_files/track.asf", "assign_asf_tags_not_asf");
+    let result = assign_tags_to_track(&sample_tags(), &path.to_string_lossy());
+    assert!(result.is_err());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_asf_tags() {
+    let path = write_asf("read_back_asf_tags");
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    // ASF has no attribute for the track total
+    let expected = TrackTags { picture_path: None, track_total: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Asf, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,180 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::env::temp_dir;
+use std::fs::{create_dir_all, read, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::audio_format::{detect_audio_format, sniff_audio_format, AudioFormat};
+use crate::fs_utils::get_audio_files_in_directory;
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags {
+        album_artist: None,
+        composer: None,
+        genre: vec!["Indie".to_string()],
+        picture_path: None,
+        disc_number: None,
+        disc_total: None,
+        disc_subtitle: None,
+        position: None,
+        compilation: None,
+        ..crate::tests::sample_tags()
+    }
+}
+
+fn temp_test_dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("riptagger_{}", name));
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A minimal WAVE file: a fmt chunk and a few bytes of silence.
+fn wav_bytes() -> Vec<u8> {
+    let mut fmt = 1u16.to_le_bytes().to_vec();
+    fmt.extend(2u16.to_le_bytes());
+    fmt.extend(44100u32.to_le_bytes());
+    fmt.extend((44100u32 * 4).to_le_bytes());
+    fmt.extend(4u16.to_le_bytes());
+    fmt.extend(16u16.to_le_bytes());
+    let mut body = b"WAVE".to_vec();
+    body.extend(b"fmt ");
+    body.extend((fmt.len() as u32).to_le_bytes());
+    body.extend(fmt);
+    body.extend(b"data");
+    body.extend(8u32.to_le_bytes());
+    body.extend([0u8; 8]);
+    let mut file = b"RIFF".to_vec();
+    file.extend((body.len() as u32).to_le_bytes());
+    file.extend(body);
+    file
+}
+
+#[test]
+fn test_from_extension() {
+    assert_eq!(AudioFormat::from_extension("flac"), Some(AudioFormat::Flac));
+    assert_eq!(AudioFormat::from_extension("FLAC"), Some(AudioFormat::Flac));
+    assert_eq!(AudioFormat::from_extension("Mp3"), Some(AudioFormat::Mp3));
+    assert_eq!(AudioFormat::from_extension("opus"), Some(AudioFormat::Ogg));
+    assert_eq!(AudioFormat::from_extension("wma"), Some(AudioFormat::Asf));
+    assert_eq!(AudioFormat::from_extension("mpc"), Some(AudioFormat::Ape));
+    assert_eq!(AudioFormat::from_extension("aif"), Some(AudioFormat::Aiff));
+    assert_eq!(AudioFormat::from_extension("txt"), None);
+}
+
+#[test]
+fn test_from_magic() {
+    assert_eq!(AudioFormat::from_magic(b"fLaC\0\0\0\x22"), Some(AudioFormat::Flac));
+    assert_eq!(AudioFormat::from_magic(b"OggS\0\x02"), Some(AudioFormat::Ogg));
+    assert_eq!(AudioFormat::from_magic(b"\0\0\0\x20ftypM4A "), Some(AudioFormat::Mp4));
+    assert_eq!(AudioFormat::from_magic(b"RIFF\x24\0\0\0WAVEfmt "), Some(AudioFormat::Wav));
+    assert_eq!(AudioFormat::from_magic(b"FORM\0\0\0\x24AIFFCOMM"), Some(AudioFormat::Aiff));
+    assert_eq!(AudioFormat::from_magic(b"FORM\0\0\0\x24AIFCFVER"), Some(AudioFormat::Aiff));
+    assert_eq!(AudioFormat::from_magic(b"wvpk\x20\0\0\0"), Some(AudioFormat::Ape));
+    assert_eq!(AudioFormat::from_magic(b"MAC \x96\x0f"), Some(AudioFormat::Ape));
+    assert_eq!(AudioFormat::from_magic(b"MPCKSH"), Some(AudioFormat::Ape));
+    assert_eq!(AudioFormat::from_magic(&[0xFF, 0xFB, 0x90, 0x64]), Some(AudioFormat::Mp3));
+    assert_eq!(
+        AudioFormat::from_magic(&[
+            0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+        ]),
+        Some(AudioFormat::Asf)
+    );
+    // AAC ADTS shares the sync word but has layer bits of 00
+    assert_eq!(AudioFormat::from_magic(&[0xFF, 0xF1, 0x50, 0x80]), None);
+    assert_eq!(AudioFormat::from_magic(b"RIFF\x24\0\0\0AVI "), None);
+    assert_eq!(AudioFormat::from_magic(b""), None);
+    assert_eq!(AudioFormat::from_magic(b"hello"), None);
+}
+
+#[test]
+fn test_sniff_skips_id3_tag() {
+    let dir = temp_test_dir("sniff_skips_id3_tag");
+
+    // an ID3v2 tag of 16 bytes in front of a FLAC stream
+    let mut flac = b"ID3\x04\0\0\0\0\0\x10".to_vec();
+    flac.extend([0u8; 16]);
+    flac.extend(b"fLaC\0\0\0\x22");
+    let flac_path = dir.join("tagged.flac");
+    write(&flac_path, flac).unwrap();
+    assert_eq!(sniff_audio_format(&flac_path).unwrap(), Some(AudioFormat::Flac));
+
+    // an ID3v2 tag with nothing recognisable after it is taken to be MP3
+    let mut mp3 = b"ID3\x04\0\0\0\0\0\x04".to_vec();
+    mp3.extend([0u8; 4]);
+    let mp3_path = dir.join("tagged.mp3");
+    write(&mp3_path, mp3).unwrap();
+    assert_eq!(sniff_audio_format(&mp3_path).unwrap(), Some(AudioFormat::Mp3));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_detect_mismatched_extension() {
+    let dir = temp_test_dir("detect_mismatched_extension");
+    let path = dir.join("track.flac");
+    write(&path, wav_bytes()).unwrap();
+
+    let detected = detect_audio_format(&path).unwrap();
+    assert_eq!(detected.content, Some(AudioFormat::Wav));
+    assert_eq!(detected.extension, Some(AudioFormat::Flac));
+    assert_eq!(detected.format(), Some(AudioFormat::Wav));
+    assert!(detected.is_mismatched());
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_detect_falls_back_to_extension() {
+    let detected = detect_audio_format(Path::new("test_data/empty_audio_files/track.flac")).unwrap();
+    assert_eq!(detected.content, None);
+    assert_eq!(detected.format(), Some(AudioFormat::Flac));
+    assert!(!detected.is_mismatched());
+}
+
+#[test]
+fn test_assign_tags_routes_by_content() {
+    let dir = temp_test_dir("assign_tags_routes_by_content");
+    let path = dir.join("track.flac");
+    write(&path, wav_bytes()).unwrap();
+
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    // tagged as WAV, so the RIFF container gained an INFO list
+    let file = read(&path).unwrap();
+    assert!(file.starts_with(b"RIFF"));
+    assert!(file.windows(4).any(|w| w == b"INFO"));
+    assert!(file.windows(11).any(|w| w == b"Starburster"));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_assign_tags_unrecognised_file() {
+    let result = assign_tags_to_track(&sample_tags(), "test_data/empty_audio_files/text.txt");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_discovery_is_case_insensitive_and_sniffs() {
+    let dir = temp_test_dir("discovery_is_case_insensitive");
+    write(dir.join("TRACK01.FLAC"), b"").unwrap();
+    write(dir.join("Track02.Mp3"), b"").unwrap();
+    write(dir.join("track04.mp4"), b"").unwrap();
+    // no extension, but WAVE contents
+    write(dir.join("track03"), wav_bytes()).unwrap();
+    write(dir.join("notes.txt"), b"just some notes").unwrap();
+    create_dir_all(dir.join("scans.flac")).unwrap();
+    // other files are never sniffed, whatever they start with
+    write(dir.join("rip.log"), b"ID3 and friends").unwrap();
+    write(dir.join("album.cue"), [0xFF, 0xFB, 0x90, 0x00]).unwrap();
+    write(dir.join("front.jpg"), wav_bytes()).unwrap();
+
+    let files = get_audio_files_in_directory(&dir.to_string_lossy()).unwrap();
+    let mut file_names: Vec<String> = files.iter().map(|f| f.file_name().to_string_lossy().to_string()).collect();
+    file_names.sort();
+    assert_eq!(file_names, vec!["TRACK01.FLAC", "Track02.Mp3", "track03", "track04.mp4"]);
+
+    remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,89 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+use crate::config::{load_config_from_file, load_resolved_config, ConfigError, ConfigFormat};
+use crate::config_chain::resolve_config;
+
+#[test]
+fn test_extends_and_include() {
+    let tags = load_config_from_file("test_data/configs/inheritance/romance/album.toml").unwrap();
+
+    assert_eq!(tags.album_name, Some("Romance".to_string()));
+    assert_eq!(tags.artist_name, Some("Fontaines D.C".to_string()));
+    assert_eq!(tags.album_artist, Some("Fontaines D.C.".to_string()));
+    // the include's genre replaces the extended one, the config's own year replaces the extended one
+    assert_eq!(tags.genre, vec!["Indie".to_string(), "Post-Punk".to_string()]);
+    assert_eq!(tags.year, Some(2024));
+    assert_eq!(tags.tracks.len(), 3);
+    // relative to the file it's set in
+    assert_eq!(tags.picture_path, Some("test_data/configs/inheritance/romance/../artist.png".to_string()));
+}
+
+#[test]
+fn test_sources() {
+    let resolved = resolve_config("test_data/configs/inheritance/romance/album.toml", ConfigFormat::Toml).unwrap();
+    let source = |key: &str| resolved.source_of(key).map(|f| f.path.clone());
+
+    assert_eq!(source("album"), Some("test_data/configs/inheritance/romance/album.toml".to_string()));
+    assert_eq!(source("year"), Some("test_data/configs/inheritance/romance/album.toml".to_string()));
+    assert_eq!(source("artist"), Some("test_data/configs/inheritance/romance/../artist.toml".to_string()));
+    assert_eq!(source("genre"), Some("test_data/configs/inheritance/romance/../post_punk.yaml".to_string()));
+    assert_eq!(source("picture"), Some("test_data/configs/inheritance/romance/../artist.toml".to_string()));
+    assert_eq!(source("composer"), None);
+    assert!(!resolved.table.contains_key("extends"));
+    assert!(!resolved.table.contains_key("include"));
+
+    assert_eq!(
+        resolved.inherited(),
+        vec![
+            ("test_data/configs/inheritance/romance/../artist.toml", vec!["album_artist", "artist", "picture"]),
+            ("test_data/configs/inheritance/romance/../post_punk.yaml", vec!["genre"]),
+        ]
+    );
+}
+
+#[test]
+fn test_error_in_extended_file() {
+    let config_path = "test_data/configs/inheritance/romance/bad_year_album.toml";
+    match load_resolved_config(config_path, ConfigFormat::Toml) {
+        Err(ConfigError::InFile { path, diagnostics, error }) => {
+            assert_eq!(path, config_path);
+            assert!(matches!(*error, ConfigError::TypeError { .. }));
+            assert_eq!(diagnostics[0].file, "test_data/configs/inheritance/romance/../bad_year.toml");
+            assert_eq!(diagnostics[0].line, Some(2));
+        }
+        other => panic!("expected an error in the extended file, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_extends_loop() {
+    match load_config_from_file("test_data/configs/inheritance/loop_a.toml") {
+        Err(ConfigError::InFile { path, error, .. }) => {
+            assert!(path.ends_with("loop_b.toml"));
+            assert_eq!(error.key_path(), Some("include".to_string()));
+        }
+        other => panic!("expected a loop, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_extended_file() {
+    let dir = temp_dir().join("riptagger_missing_extended_file");
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    let config_path = dir.join("album.toml");
+    write(&config_path, "extends = \"artist.toml\"\ntracks = [\"Romance\"]\n").unwrap();
+
+    match load_config_from_file(&config_path.to_string_lossy()) {
+        Err(ConfigError::InFile { path, error, .. }) => {
+            assert_eq!(path, dir.join("artist.toml").to_string_lossy());
+            assert!(matches!(*error, ConfigError::Io(_)));
+        }
+        other => panic!("expected the extended file to be missing, got {:?}", other),
+    }
+
+    remove_dir_all(dir).unwrap();
+}
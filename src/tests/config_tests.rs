@@ -1,6 +1,12 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
-use crate::config::{ConfigError, load_config_from_file};
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+use crate::album_tags::AlbumTags;
+use crate::config::{
+    config_and_album_paths, find_config_file, load_config_from_file, load_config_from_file_as, ConfigError, ConfigFormat,
+};
 
 #[test]
 fn test_valid_config() {
@@ -15,7 +21,7 @@ fn test_valid_config() {
     assert_eq!(tracks.len(), 11);
     assert_eq!(
         tracks,
-        vec![
+        [
             "Romance",
             "Starburster",
             "Here's The Thing",
@@ -58,6 +64,112 @@ fn test_config_error_display() {
     let err1 = ConfigError::MissingKey("album".to_string());
     assert_eq!(format!("{}", err1), "Missing key: album");
 
-    let err2 = ConfigError::TypeError("year should be an integer".to_string());
+    let err2 = ConfigError::TypeError {
+        key: "year".to_string(),
+        message: "year should be an integer".to_string(),
+    };
     assert_eq!(format!("{}", err2), "Type error: year should be an integer");
 }
+
+#[test]
+fn test_config_format_from_path() {
+    assert_eq!(ConfigFormat::from_path("album.toml"), Some(ConfigFormat::Toml));
+    assert_eq!(ConfigFormat::from_path("album.yaml"), Some(ConfigFormat::Yaml));
+    assert_eq!(ConfigFormat::from_path("album.YML"), Some(ConfigFormat::Yaml));
+    assert_eq!(ConfigFormat::from_path("rips/album.json"), Some(ConfigFormat::Json));
+    assert_eq!(ConfigFormat::from_path("album.txt"), None);
+    assert_eq!(ConfigFormat::from_path("album"), None);
+}
+
+#[test]
+fn test_yaml_and_json_configs() {
+    let toml = load_config_from_file("test_data/configs/valid_config.toml").unwrap();
+    for config_path in ["test_data/configs/valid_config.yaml", "test_data/configs/valid_config.json"] {
+        let tags = load_config_from_file(config_path).unwrap();
+        assert_eq!(tags.album_name, toml.album_name, "{}", config_path);
+        assert_eq!(tags.year, toml.year, "{}", config_path);
+        assert_eq!(tags.genre, toml.genre, "{}", config_path);
+        assert_eq!(tags.tracks, toml.tracks, "{}", config_path);
+        assert_eq!(tags.tracks_per_disc, toml.tracks_per_disc, "{}", config_path);
+        assert_eq!(tags.picture_path, toml.picture_path, "{}", config_path);
+    }
+}
+
+#[test]
+fn test_config_format_overrides_extension() {
+    let dir = temp_dir().join("riptagger_config_format_overrides_extension");
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    let config_path = dir.join("album.conf");
+    write(&config_path, "album: Romance\ntracks: [Romance, Starburster]\n").unwrap();
+    let config_path = config_path.to_string_lossy();
+
+    assert!(matches!(load_config_from_file(&config_path), Err(ConfigError::Parse(_))));
+    let tags = load_config_from_file_as(&config_path, ConfigFormat::Yaml).unwrap();
+    assert_eq!(tags.album_name, Some("Romance".to_string()));
+    assert_eq!(tags.tracks.len(), 2);
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_yaml_and_json_errors() {
+    assert!(matches!(ConfigFormat::Yaml.parse("tracks: [Romance"), Err(ConfigError::Yaml(_))));
+    assert!(matches!(ConfigFormat::Json.parse("{\"tracks\": "), Err(ConfigError::Json(_))));
+
+    // validated like TOML once parsed
+    let table = ConfigFormat::Json.parse(r#"{"tracks": ["Romance"], "year": "2024"}"#).unwrap();
+    let error = AlbumTags::from_toml(table).unwrap_err();
+    assert_eq!(error.to_string(), "Type error: year should be an integer");
+}
+
+#[test]
+fn test_find_config_file() {
+    let dir = temp_dir().join("riptagger_find_config_file");
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    assert_eq!(find_config_file(&dir), None);
+
+    write(dir.join("album.toml"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join("album.toml")));
+    write(dir.join(".riptagger.toml"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join(".riptagger.toml")));
+    write(dir.join("riptagger.json"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join("riptagger.json")));
+    write(dir.join("riptagger.yml"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join("riptagger.yml")));
+    write(dir.join("riptagger.yaml"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join("riptagger.yaml")));
+    write(dir.join("riptagger.toml"), "").unwrap();
+    assert_eq!(find_config_file(&dir), Some(dir.join("riptagger.toml")));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_config_and_album_paths() {
+    let paths = |config: Option<&str>, album: Option<&str>| config_and_album_paths(config, album);
+
+    assert_eq!(
+        paths(Some("album.toml"), Some("rips/romance")),
+        Some(("album.toml".to_string(), "rips/romance".to_string()))
+    );
+    assert_eq!(
+        paths(Some("rips/romance/album.toml"), None),
+        Some(("rips/romance/album.toml".to_string(), "rips/romance".to_string()))
+    );
+    assert_eq!(paths(Some("album.toml"), None), Some(("album.toml".to_string(), ".".to_string())));
+
+    let dir = temp_dir().join("riptagger_config_and_album_paths");
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    let album = dir.to_string_lossy().to_string();
+    assert_eq!(paths(None, Some(&album)), None);
+    write(dir.join("riptagger.toml"), "").unwrap();
+    assert_eq!(
+        paths(None, Some(&album)),
+        Some((dir.join("riptagger.toml").to_string_lossy().to_string(), album.clone()))
+    );
+
+    remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,122 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+use toml::Table;
+
+use crate::album_tags::AlbumTags;
+use crate::config::{load_config_from_file, ConfigError, ConfigFormat};
+use crate::diagnostic::diagnose;
+
+fn diagnose_source(source: &str) -> Vec<crate::diagnostic::Diagnostic> {
+    let table: Table = source.parse().unwrap();
+    let error = AlbumTags::from_toml(table).unwrap_err();
+    diagnose("album.toml", source, ConfigFormat::Toml, &error)
+}
+
+#[test]
+fn test_type_error_points_at_value() {
+    let diagnostics = diagnose_source("album = \"Romance\"\nyear = \"2024\"\ntracks = [\"Romance\"]\n");
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!(d.line, Some(2));
+    assert_eq!(d.column, 8);
+    assert_eq!(d.highlight_len, 6);
+    assert_eq!(d.source_line, "year = \"2024\"");
+    assert_eq!(
+        d.to_string(),
+        "error: Type error: year should be an integer\n \
+         --> album.toml:2:8\n  \
+         |\n\
+         2 | year = \"2024\"\n  \
+         |        ^^^^^^\n  \
+         = hint: numbers are written without quotes, as in year = 2024"
+    );
+}
+
+#[test]
+fn test_unknown_key_points_at_key() {
+    let diagnostics = diagnose_source("artsit = \"Fontaines D.C\"\ntracks = [\"Romance\"]\n");
+    let d = &diagnostics[0];
+    assert_eq!((d.line, d.column, d.highlight_len), (Some(1), 1, 6));
+    assert!(d.message.contains("did you mean 'artist'?"));
+}
+
+#[test]
+fn test_error_without_key_in_message_points_at_key() {
+    let source = "[[disc]]\ntracks = [\"Romance\"]\n\n[[side]]\ntracks = [\"Starburster\"]\n";
+    let d = &diagnose_source(source)[0];
+    assert_eq!(d.message, "Type error: use either [[disc]] or [[side]] tables, not both");
+    assert_eq!(d.line, Some(4));
+    assert_eq!(d.source_line, "[[side]]");
+}
+
+#[test]
+fn test_missing_nested_key_points_at_table() {
+    let source = "[[track]]\ntitle = \"One\"\n\n[[track]]\nartist = \"Someone\"\n";
+    let d = &diagnose_source(source)[0];
+    assert_eq!(d.message, "Missing key: track[1].title");
+    assert_eq!(d.line, Some(4));
+    assert_eq!(d.source_line, "[[track]]");
+    assert_eq!(d.hint, Some("add 'title' to this table".to_string()));
+}
+
+#[test]
+fn test_missing_top_level_key() {
+    let d = &diagnose_source("album = \"Romance\"\n")[0];
+    assert_eq!(d.line, None);
+    assert_eq!(d.to_string(), "error: Missing key: tracks\n --> album.toml\n  = hint: add 'tracks' to the config");
+}
+
+#[test]
+fn test_problem_deep_in_long_tracklist() {
+    let mut source = String::from("album = \"Long\"\n");
+    for i in 0..20 {
+        source.push_str(&format!("\n[[track]]\ntitle = \"Track {}\"\n", i));
+    }
+    source.push_str("year = 2020\ncomposr = \"Someone\"\n");
+    let diagnostics = diagnose_source(&source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Unknown key: track[19].composr (did you mean 'track[19].composer'?)");
+    assert_eq!(diagnostics[0].line, Some(63));
+    assert_eq!(diagnostics[0].source_line, "composr = \"Someone\"");
+}
+
+#[test]
+fn test_load_config_from_file_diagnostics() {
+    let dir = std::env::temp_dir().join("riptagger_config_diagnostics");
+    create_dir_all(&dir).unwrap();
+    let path = dir.join("album.toml");
+    write(&path, "tracks = [\"One\"]\ndisc_total = \"2\"\n").unwrap();
+
+    let error = load_config_from_file(&path.to_string_lossy()).unwrap_err();
+    assert!(matches!(error.inner(), ConfigError::TypeError { .. }));
+    assert_eq!(error.key_path(), Some("disc_total".to_string()));
+    assert_eq!(format!("{}", error), "Type error: disc_total should be an integer");
+    let shown = format!("{:#}", error);
+    assert!(shown.contains(&format!("--> {}:2:14", path.to_string_lossy())));
+    assert!(shown.contains("2 | disc_total = \"2\""));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_yaml_config_diagnostics_name_the_file() {
+    let dir = std::env::temp_dir().join("riptagger_yaml_config_diagnostics");
+    create_dir_all(&dir).unwrap();
+    let path = dir.join("riptagger.yaml");
+    write(&path, "tracks:\n  - One\ndisc_total: \"2\"\n").unwrap();
+
+    let error = load_config_from_file(&path.to_string_lossy()).unwrap_err();
+    assert_eq!(format!("{}", error), "Type error: disc_total should be an integer");
+    match &error {
+        ConfigError::InFile { diagnostics, .. } => {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].line, None);
+            assert_eq!(diagnostics[0].file, path.to_string_lossy());
+        }
+        other => panic!("expected an error in the file, got {:?}", other),
+    }
+
+    remove_dir_all(dir).unwrap();
+}
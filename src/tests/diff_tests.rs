@@ -0,0 +1,120 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all};
+
+use crate::diff::{field_changes, FieldChange};
+use crate::pipeline::{PlanOptions, TrackPlan};
+use crate::tag_writer::TagWriterRegistry;
+use crate::tests::copy_to_temp;
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags {
+        album_artist: None,
+        composer: None,
+        disc_number: None,
+        disc_total: None,
+        disc_subtitle: None,
+        position: None,
+        compilation: None,
+        ..crate::tests::sample_tags()
+    }
+}
+
+#[test]
+fn test_field_changes() {
+    let before = TrackTags {
+        track_name: "Track 2".to_string(),
+        year: Some(2024),
+        ..TrackTags::default()
+    };
+    let changes = field_changes(&before, &sample_tags());
+    assert_eq!(changes[0], FieldChange {
+        field: "album",
+        before: None,
+        after: Some("Romance".to_string()),
+    });
+    assert!(changes.contains(&FieldChange {
+        field: "title",
+        before: Some("Track 2".to_string()),
+        after: Some("Starburster".to_string()),
+    }));
+    assert!(changes.contains(&FieldChange {
+        field: "genre",
+        before: None,
+        after: Some("Indie; Post-Punk".to_string()),
+    }));
+    // unchanged fields are left out
+    assert!(!changes.iter().any(|c| c.field == "year" || c.field == "disc number"));
+}
+
+#[test]
+fn test_preview_does_not_write() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "preview_does_not_write");
+    let original = read(&path).unwrap();
+    let plan = TrackPlan::new(path.clone(), sample_tags(), &PlanOptions::default()).unwrap();
+
+    let diff = plan.preview(&TagWriterRegistry::default()).unwrap();
+    assert_eq!(diff.changes.len(), 7);
+    assert_eq!(diff.new_name, Some("02 - Starburster.mp3".to_string()));
+    assert_eq!(read(&path).unwrap(), original);
+
+    let shown = format!("{}", diff);
+    assert!(shown.contains("\n  title: (none) -> \"Starburster\""));
+    assert!(shown.contains("\n  front cover: embed 'test_data/pictures/cover.png'"));
+    assert!(shown.ends_with("\n  rename to '02 - Starburster.mp3'"));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_preview_already_tagged() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "preview_already_tagged");
+    let tags = TrackTags { picture_path: None, ..sample_tags() };
+    assign_tags_to_track(&tags, &path.to_string_lossy()).unwrap();
+
+    let options = PlanOptions {
+        rename_files: false,
+        ..PlanOptions::default()
+    };
+    let plan = TrackPlan::new(path.clone(), tags, &options).unwrap();
+    let diff = plan.preview(&TagWriterRegistry::default()).unwrap();
+    assert!(diff.is_empty());
+    assert!(format!("{}", diff).ends_with("\n  no changes"));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_preview_already_tagged_custom_position_tag() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "preview_already_tagged_position_tag");
+    let tags = TrackTags {
+        picture_path: None,
+        position: Some("A2".to_string()),
+        position_tag: Some("SIDEPOSITION".to_string()),
+        ..sample_tags()
+    };
+    assign_tags_to_track(&tags, &path.to_string_lossy()).unwrap();
+
+    let options = PlanOptions {
+        rename_files: false,
+        ..PlanOptions::default()
+    };
+    let plan = TrackPlan::new(path.clone(), tags, &options).unwrap();
+    assert!(plan.preview(&TagWriterRegistry::default()).unwrap().is_empty());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_preview_missing_picture() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "preview_missing_picture");
+    let tags = TrackTags {
+        picture_path: Some("test_data/pictures/missing.png".to_string()),
+        ..sample_tags()
+    };
+    let plan = TrackPlan::new(path.clone(), tags, &PlanOptions::default()).unwrap();
+    assert!(plan.preview(&TagWriterRegistry::default()).is_err());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
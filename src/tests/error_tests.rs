@@ -0,0 +1,103 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::remove_dir_all;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::{load_config_from_file, ConfigError};
+use crate::error::{exit_code, RiptaggerError};
+use crate::pipeline::{Album, ApplyError, PlanError, PlanOptions, TrackPlan};
+use crate::tag_writer::TagWriterRegistry;
+use crate::tests::copy_to_temp;
+use crate::track_tags::{TagError, TrackTags};
+
+fn tags_with_picture(picture_path: &str) -> TrackTags {
+    TrackTags {
+        track_name: "Starburster".to_string(),
+        picture_path: Some(picture_path.to_string()),
+        track_number: Some(2),
+        ..TrackTags::default()
+    }
+}
+
+#[test]
+fn test_config_exit_code() {
+    let error = load_config_from_file("non_existent.toml").unwrap_err();
+    assert!(matches!(error, ConfigError::Io(_)));
+
+    let error = RiptaggerError::config("non_existent.toml", error);
+    assert_eq!(error.exit_code(), exit_code::CONFIG);
+    assert!(format!("{}", error).starts_with("Could not read config file 'non_existent.toml'"));
+}
+
+#[test]
+fn test_no_config_exit_code() {
+    let error = RiptaggerError::NoConfig { directory: "rips/romance".to_string() };
+    assert_eq!(error.exit_code(), exit_code::CONFIG);
+    assert_eq!(
+        format!("{}", error),
+        "No config file given and none found in 'rips/romance', looked for riptagger.toml, riptagger.yaml, riptagger.yml, riptagger.json, .riptagger.toml, album.toml"
+    );
+}
+
+#[test]
+fn test_discovery_exit_code() {
+    let album = Album::new(load_config_from_file("test_data/configs/valid_config.toml").unwrap(), "test_data/empty_audio_files");
+    let error: RiptaggerError = album.plan(&PlanOptions::default()).unwrap_err().into();
+    assert!(matches!(error, RiptaggerError::Discovery(PlanError::TrackCountMismatch { .. })));
+    assert_eq!(error.exit_code(), exit_code::DISCOVERY);
+}
+
+#[test]
+fn test_picture_too_large_exit_code() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "picture_too_large_exit_code");
+    let options = PlanOptions {
+        max_picture_size: Some(10),
+        ..PlanOptions::default()
+    };
+    let error: RiptaggerError = TrackPlan::new(path.clone(), tags_with_picture("test_data/pictures/cover.png"), &options)
+        .unwrap_err()
+        .into();
+    assert_eq!(error.exit_code(), exit_code::PICTURE);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_missing_picture_exit_code() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "missing_picture_exit_code");
+    let plan = TrackPlan::new(path.clone(), tags_with_picture("test_data/pictures/missing.png"), &PlanOptions::default()).unwrap();
+
+    let error = plan.apply(&TagWriterRegistry::default()).unwrap_err();
+    assert!(matches!(error, ApplyError::Tag(TagError::Picture(..))));
+    let error = RiptaggerError::track(plan.path.clone(), error);
+    assert_eq!(error.exit_code(), exit_code::PICTURE);
+    // the file is left where it was
+    assert!(path.exists());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_track_exit_codes() {
+    let path = PathBuf::from("track.flac");
+    let tag = RiptaggerError::track(path.clone(), ApplyError::Tag(TagError::InvalidFile("bad".to_string())));
+    assert_eq!(tag.exit_code(), exit_code::TAG);
+
+    let rename = RiptaggerError::track(path.clone(), ApplyError::Rename(io::Error::from(io::ErrorKind::PermissionDenied)));
+    assert_eq!(rename.exit_code(), exit_code::RENAME);
+    assert!(format!("{}", rename).starts_with("Error renaming file 'track.flac'"));
+}
+
+#[test]
+fn test_tracks_failed_exit_code() {
+    let path = PathBuf::from("track.flac");
+    let failed = vec![
+        RiptaggerError::track(path.clone(), ApplyError::Rename(io::Error::from(io::ErrorKind::NotFound))),
+        RiptaggerError::track(path.clone(), ApplyError::Tag(TagError::InvalidFile("bad".to_string()))),
+    ];
+    let error = RiptaggerError::TracksFailed { failed, total: 11 };
+    // the first failure decides the exit code
+    assert_eq!(error.exit_code(), exit_code::RENAME);
+    assert_eq!(format!("{}", error), "2 of 11 tracks failed");
+}
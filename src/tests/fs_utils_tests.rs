@@ -1,36 +1,31 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 use std::env::temp_dir;
-use std::io;
-use std::fs::{copy, read_dir, remove_dir_all, create_dir_all};
+use std::fs::{remove_dir_all, create_dir_all, write};
 use std::path::Path;
 
 use crate::fs_utils;
 
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-    create_dir_all(&dst)?;
-    for entry in read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
-    }
-    Ok(())
-}
-
 #[test]
 fn test_get_audio_files_in_directory() {
     let path = "test_data/empty_audio_files";
     let files = fs_utils::get_audio_files_in_directory(path).unwrap();
-    assert_eq!(files.len(), 4);
+    assert_eq!(files.len(), 14);
     let file_names: Vec<String> = files.iter().map(|f| f.file_name().to_string_lossy().to_string()).collect();
     assert!(file_names.contains(&"track.mp3".to_string()));
     assert!(file_names.contains(&"track.ogg".to_string()));
+    assert!(file_names.contains(&"track.oga".to_string()));
+    assert!(file_names.contains(&"track.opus".to_string()));
     assert!(file_names.contains(&"track.flac".to_string()));
+    assert!(file_names.contains(&"track.m4a".to_string()));
+    assert!(file_names.contains(&"track.wv".to_string()));
+    assert!(file_names.contains(&"track.ape".to_string()));
+    assert!(file_names.contains(&"track.mpc".to_string()));
+    assert!(file_names.contains(&"track.wav".to_string()));
+    assert!(file_names.contains(&"track.aiff".to_string()));
+    assert!(file_names.contains(&"track.aif".to_string()));
     assert!(file_names.contains(&"track.asf".to_string()));
+    assert!(file_names.contains(&"track.wma".to_string()));
     assert!(!file_names.contains(&"text.txt".to_string()));
 }
 
@@ -50,23 +45,48 @@ fn test_normalize_file_name() {
 }
 
 #[test]
-fn test_rename_audio_file() {
-    let original_path = Path::new("test_data/test_rename_audio_file");
-    let path = temp_dir().join("riptagger_test");
+fn test_audio_file_name_with_disc() {
+    assert_eq!(fs_utils::audio_file_name("dir/track.flac", Some(1), 1, "First"), "1-01 - First.flac");
+    assert_eq!(fs_utils::audio_file_name("dir/track.flac", Some(2), 12, "Twelfth"), "2-12 - Twelfth.flac");
+    assert_eq!(fs_utils::audio_file_name("dir/track.flac", None, 3, "Third"), "03 - Third.flac");
+}
 
-    copy_dir_all(&original_path, &path).unwrap();
-    
-    fs_utils::rename_audio_file(&path.join("first.mp3").to_string_lossy(), 1, "First").unwrap();
-    fs_utils::rename_audio_file(&path.join("second.mp3").to_string_lossy(), 2, "Second").unwrap();
-    fs_utils::rename_audio_file(&path.join("third.mp3").to_string_lossy(), 3, "Third").unwrap();
-    
-    let files = fs_utils::get_audio_files_in_directory(&path.to_string_lossy()).unwrap();
-    assert_eq!(files.len(), 3);
-    let file_names: Vec<String> = files.iter().map(|f| f.file_name().to_string_lossy().to_string()).collect();
-    assert!(file_names.contains(&"01 - First.mp3".to_string()));
-    assert!(file_names.contains(&"02 - Second.mp3".to_string()));
-    assert!(file_names.contains(&"03 - Third.mp3".to_string()));
+#[test]
+fn test_file_name_at() {
+    assert_eq!(fs_utils::file_name_at("dir/track.flac", "A1", "First"), "A1 - First.flac");
+    assert_eq!(fs_utils::file_name_at("dir/track.flac", "B12", "Last?"), "B12 - Last.flac");
+}
+
+#[test]
+fn test_find_cover_picture() {
+    let path = temp_dir().join("riptagger_find_cover_picture");
+    let _ = remove_dir_all(&path);
+    create_dir_all(&path).unwrap();
+    assert_eq!(fs_utils::find_cover_picture(&path), None);
+
+    // not a picture, and not a cover
+    write(path.join("cover.txt"), "").unwrap();
+    write(path.join("back.jpg"), "").unwrap();
+    assert_eq!(fs_utils::find_cover_picture(&path), None);
+
+    write(path.join("Front.JPG"), "").unwrap();
+    assert_eq!(fs_utils::find_cover_picture(&path), Some(path.join("Front.JPG")));
+    write(path.join("folder.png"), "").unwrap();
+    assert_eq!(fs_utils::find_cover_picture(&path), Some(path.join("folder.png")));
+    write(path.join("cover.jpeg"), "").unwrap();
+    assert_eq!(fs_utils::find_cover_picture(&path), Some(path.join("cover.jpeg")));
 
-    // cleanup
     remove_dir_all(path).unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_resolve_config_path() {
+    let home = std::env::var("HOME").unwrap();
+    let dir = Path::new("rips/romance");
+    assert_eq!(fs_utils::resolve_config_path("cover.jpg", dir), dir.join("cover.jpg"));
+    assert_eq!(fs_utils::resolve_config_path("../art/cover.jpg", dir), dir.join("../art/cover.jpg"));
+    assert_eq!(fs_utils::resolve_config_path("/srv/art/cover.jpg", dir), Path::new("/srv/art/cover.jpg"));
+    assert_eq!(fs_utils::resolve_config_path("~/art/cover.jpg", dir), Path::new(&home).join("art/cover.jpg"));
+    // only a leading `~/` is the home directory
+    assert_eq!(fs_utils::resolve_config_path("~art/cover.jpg", dir), dir.join("~art/cover.jpg"));
+}
@@ -0,0 +1,113 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::remove_dir_all;
+
+use id3::{Tag, TagLike, Version};
+use id3::frame::PictureType;
+
+use crate::audio_format::AudioFormat;
+use crate::id3_tags::Id3Writer;
+use crate::tag_writer::TagWriter;
+use crate::tests::{copy_to_temp, read_back};
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags { disc_total: Some(1), ..crate::tests::sample_tags() }
+}
+
+#[test]
+fn test_assign_id3_tags() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "assign_id3_tags");
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let tag = Tag::read_from_path(&path).unwrap();
+    assert_eq!(tag.title(), Some("Starburster"));
+    assert_eq!(tag.album(), Some("Romance"));
+    assert_eq!(tag.artist(), Some("Fontaines D.C"));
+    assert_eq!(tag.get("TRCK").and_then(|f| f.content().text()), Some("2/11"));
+    assert_eq!(tag.get("TPOS").and_then(|f| f.content().text()), Some("1/1"));
+    assert_eq!(tag.date_recorded().map(|t| t.year), Some(2024));
+    assert_eq!(tag.genres(), Some(vec!["Indie", "Post-Punk"]));
+
+    let pictures: Vec<_> = tag.pictures().collect();
+    assert_eq!(pictures.len(), 1);
+    assert_eq!(pictures[0].picture_type, PictureType::CoverFront);
+    assert_eq!(pictures[0].mime_type, "image/png");
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_id3_tags_twice_keeps_one_cover() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "assign_id3_tags_twice");
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let tag = Tag::read_from_path(&path).unwrap();
+    assert_eq!(tag.pictures().count(), 1);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_id3_tags() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "read_back_id3_tags");
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    let expected = TrackTags { picture_path: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Mp3, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_id3_position_tag() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "assign_id3_position_tag");
+    let tags = TrackTags {
+        position_tag: Some("DISCOGS_POSITION".to_string()),
+        ..sample_tags()
+    };
+    assign_tags_to_track(&tags, &path.to_string_lossy()).unwrap();
+    assign_tags_to_track(&tags, &path.to_string_lossy()).unwrap();
+
+    let tag = Tag::read_from_path(&path).unwrap();
+    let positions: Vec<_> = tag
+        .extended_texts()
+        .filter(|t| t.description == "DISCOGS_POSITION")
+        .map(|t| t.value.as_str())
+        .collect();
+    assert_eq!(positions, vec!["A2"]);
+    assert!(!tag.extended_texts().any(|t| t.description == "VINYLTRACK"));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_write_id3v23_tags() {
+    let path = copy_to_temp("test_data/empty_audio_files/track.mp3", "write_id3v23_tags");
+    let writer = Id3Writer { version: Version::Id3v23 };
+    let mut file = writer.open(&path.to_string_lossy()).unwrap();
+    file.apply(&sample_tags()).unwrap();
+    file.save().unwrap();
+
+    let tag = Tag::read_from_path(&path).unwrap();
+    assert_eq!(tag.version(), Version::Id3v23);
+    assert_eq!(tag.title(), Some("Starburster"));
+    // TDRC is ID3v2.4 only
+    assert_eq!(tag.year(), Some(2024));
+    assert!(tag.get("TDRC").is_none());
+    assert_eq!(read_back(AudioFormat::Mp3, &path).year, Some(2024));
+    // neither is TSST
+    assert!(tag.get("TSST").is_none());
+    assert_eq!(
+        tag.extended_texts().find(|t| t.description == "DISCSUBTITLE").map(|t| t.value.as_str()),
+        Some("Side A")
+    );
+    assert_eq!(read_back(AudioFormat::Mp3, &path).disc_subtitle, Some("Side A".to_string()));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
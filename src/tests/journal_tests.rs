@@ -0,0 +1,218 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::env::temp_dir;
+use std::fs::{copy, create_dir_all, read, read_dir, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::album_tags::AlbumTags;
+use crate::journal::{undo, Journal, JournalError, TaggedRegion, JOURNAL_DIR, PENDING_JOURNAL_DIR};
+use crate::pipeline::{apply_with_journal, Album, PlanOptions};
+use crate::tag_writer::TagWriterRegistry;
+
+const FILES: [&str; 3] = ["first.mp3", "second.mp3", "third.mp3"];
+
+fn album_tags() -> AlbumTags {
+    AlbumTags {
+        picture_path: Some("test_data/pictures/cover.png".to_string()),
+        ..crate::tests::album_tags(&["Romance", "Starburster", "Here's The Thing"])
+    }
+}
+
+fn album_dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("riptagger_{}", name));
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    for file in FILES {
+        copy(format!("test_data/test_rename_audio_file/{}", file), dir.join(file)).unwrap();
+    }
+    dir
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Tag and rename every track in `dir`, journaling the changes.
+fn tag_album(dir: &Path) {
+    tag_album_with(dir, album_tags(), &PlanOptions::default());
+}
+
+fn tag_album_with(dir: &Path, tags: AlbumTags, options: &PlanOptions) {
+    let plans = Album::new(tags, dir).plan(options).unwrap();
+    let mut journal = Journal::begin(dir).unwrap();
+    for result in apply_with_journal(&plans, &TagWriterRegistry::default(), &mut journal) {
+        result.unwrap();
+    }
+    journal.finish().unwrap();
+}
+
+#[test]
+fn test_undo_restores_tags_and_names() {
+    let dir = album_dir("undo_restores_tags_and_names");
+    let originals: Vec<Vec<u8>> = FILES.iter().map(|f| read(dir.join(f)).unwrap()).collect();
+
+    tag_album(&dir);
+    assert_eq!(
+        file_names(&dir),
+        vec![JOURNAL_DIR, "01 - Romance.mp3", "02 - Starburster.mp3", "03 - Heres The Thing.mp3"]
+    );
+
+    let restored = undo(&dir).unwrap();
+    assert_eq!(restored.len(), 3);
+    assert_eq!(file_names(&dir), FILES);
+    for (file, original) in FILES.iter().zip(originals) {
+        assert_eq!(read(dir.join(file)).unwrap(), original);
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_journal_round_trip() {
+    let dir = album_dir("journal_round_trip");
+    tag_album(&dir);
+
+    let journal = Journal::open(&dir).unwrap();
+    assert_eq!(journal.entries.len(), 3);
+    assert_eq!(journal.entries[1].original_name, "second.mp3");
+    assert_eq!(journal.entries[1].new_name, "02 - Starburster.mp3");
+    // the tag is written at the start of an mp3, the audio is left alone
+    let region = journal.entries[1].region.as_ref().unwrap();
+    assert_eq!(region.prefix_len, 0);
+    assert!(region.tagged_len > read("test_data/test_rename_audio_file/second.mp3").unwrap().len() as u64);
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_record_tagged_keeps_changed_bytes() {
+    let dir = temp_dir().join("riptagger_record_tagged_keeps_changed_bytes");
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+
+    let mut journal = Journal::begin(&dir).unwrap();
+    let original = b"header OLD TAG audio audio audio";
+    let tagged = b"header A NEW, LONGER TAG audio audio audio";
+    let index = journal.record_original(&dir.join("track.flac"), original).unwrap();
+    journal.record_tagged(index, original, tagged).unwrap();
+
+    let region = journal.entries[index].region.clone().unwrap();
+    assert_eq!(region.prefix_len, 7);
+    assert_eq!(region.suffix_len, 22);
+    let backup = read(dir.join(PENDING_JOURNAL_DIR).join(&journal.entries[index].backup)).unwrap();
+    assert_eq!(backup, b"OLD");
+
+    // the tagged file is restored from the region
+    write(dir.join("track.flac"), tagged).unwrap();
+    undo(&dir).unwrap();
+    assert_eq!(read(dir.join("track.flac")).unwrap(), original);
+    assert!(!dir.join(PENDING_JOURNAL_DIR).exists());
+    assert!(!dir.join(JOURNAL_DIR).exists());
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_undo_interrupted_run() {
+    let dir = album_dir("undo_interrupted_run");
+    let original = read(dir.join("first.mp3")).unwrap();
+
+    // the run stopped after the backup, before the tagged file was recorded
+    let mut journal = Journal::begin(&dir).unwrap();
+    journal.record_original(&dir.join("first.mp3"), &original).unwrap();
+    assert_eq!(journal.entries[0].region, None::<TaggedRegion>);
+    write(dir.join("first.mp3"), b"half written").unwrap();
+
+    undo(&dir).unwrap();
+    assert_eq!(read(dir.join("first.mp3")).unwrap(), original);
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_undo_modified_file() {
+    let dir = album_dir("undo_modified_file");
+    tag_album(&dir);
+    write(dir.join("02 - Starburster.mp3"), b"edited by hand").unwrap();
+
+    assert!(matches!(undo(&dir), Err(JournalError::Modified(_))));
+    // nothing was restored
+    assert!(dir.join("01 - Romance.mp3").exists());
+    assert!(dir.join(JOURNAL_DIR).exists());
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_undo_without_journal() {
+    let dir = album_dir("undo_without_journal");
+    assert!(matches!(undo(&dir), Err(JournalError::NotFound(_))));
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_undo_rerun_with_swapped_tracks() {
+    let dir = album_dir("undo_rerun_with_swapped_tracks");
+    let options = PlanOptions {
+        file_name_template: Some("{title}".to_string()),
+        ..PlanOptions::default()
+    };
+    tag_album_with(&dir, album_tags(), &options);
+    let names = ["Heres The Thing.mp3", "Romance.mp3", "Starburster.mp3"];
+    assert_eq!(file_names(&dir), [JOURNAL_DIR, names[0], names[1], names[2]]);
+    let tagged: Vec<Vec<u8>> = names.iter().map(|f| read(dir.join(f)).unwrap()).collect();
+
+    // in file name order the first file is now Starburster and the last Here's The
+    // Thing, so they swap names and Starburster.mp3 becomes track 1
+    let tags = AlbumTags {
+        tracks: vec!["Starburster".to_string(), "Romance".to_string(), "Here's The Thing".to_string()],
+        ..album_tags()
+    };
+    tag_album_with(&dir, tags, &options);
+    assert_eq!(file_names(&dir), [JOURNAL_DIR, names[0], names[1], names[2]]);
+    assert_ne!(read(dir.join(names[2])).unwrap(), tagged[2]);
+
+    undo(&dir).unwrap();
+    assert_eq!(file_names(&dir), names);
+    for (file, tagged) in names.iter().zip(tagged) {
+        assert_eq!(read(dir.join(file)).unwrap(), tagged);
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_failed_rerun_keeps_last_journal() {
+    let dir = album_dir("failed_rerun_keeps_last_journal");
+    let originals: Vec<Vec<u8>> = FILES.iter().map(|f| read(dir.join(f)).unwrap()).collect();
+    tag_album(&dir);
+
+    // a run that fails before changing anything
+    drop(Journal::begin(&dir).unwrap());
+    assert!(dir.join(JOURNAL_DIR).exists());
+
+    undo(&dir).unwrap();
+    for (file, original) in FILES.iter().zip(originals) {
+        assert_eq!(read(dir.join(file)).unwrap(), original);
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_begin_after_interrupted_run() {
+    let dir = album_dir("begin_after_interrupted_run");
+    let mut journal = Journal::begin(&dir).unwrap();
+    journal.record_original(&dir.join("first.mp3"), b"original").unwrap();
+
+    assert!(matches!(Journal::begin(&dir), Err(JournalError::Interrupted(_))));
+    undo(&dir).unwrap();
+    assert!(Journal::begin(&dir).is_ok());
+
+    remove_dir_all(dir).unwrap();
+}
@@ -1,22 +1,153 @@
 // Copyright: (c) 2025, Colm Murphy
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 #[cfg(test)]
-pub mod tests {
+pub mod smoke_tests {
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
 }
 
+#[cfg(test)]
+pub mod album_config_tests;
+
 #[cfg(test)]
 pub mod album_tags_tests;
 
+#[cfg(test)]
+pub mod ape_tags_tests;
+
+#[cfg(test)]
+pub mod audio_format_tests;
+
+#[cfg(test)]
+pub mod asf_tags_tests;
+
+#[cfg(test)]
+pub mod config_chain_tests;
+
 #[cfg(test)]
 pub mod config_tests;
 
+#[cfg(test)]
+pub mod diagnostic_tests;
+
+#[cfg(test)]
+pub mod diff_tests;
+
+#[cfg(test)]
+pub mod error_tests;
+
 #[cfg(test)]
 pub mod fs_utils_tests;
 
 #[cfg(test)]
-pub mod toml_helpers_tests;
+pub mod id3_tags_tests;
+
+#[cfg(test)]
+pub mod journal_tests;
+
+#[cfg(test)]
+pub mod mp4_tags_tests;
+
+#[cfg(test)]
+pub mod ogg_tags_tests;
+
+#[cfg(test)]
+pub mod pipeline_tests;
+
+#[cfg(test)]
+pub mod riff_tags_tests;
 
+#[cfg(test)]
+pub mod settings_tests;
+
+#[cfg(test)]
+pub mod tag_writer_tests;
+
+#[cfg(test)]
+pub mod validation_tests;
+
+
+/// Tags for a track with every field the backends write set.
+#[cfg(test)]
+pub fn sample_tags() -> crate::track_tags::TrackTags {
+    crate::track_tags::TrackTags {
+        album_name: Some("Romance".to_string()),
+        artist_name: Some("Fontaines D.C".to_string()),
+        album_artist: Some("Fontaines D.C.".to_string()),
+        composer: Some("Grian Chatten".to_string()),
+        year: Some(2024),
+        track_name: "Starburster".to_string(),
+        genre: vec!["Indie".to_string(), "Post-Punk".to_string()],
+        picture_path: Some("test_data/pictures/cover.png".to_string()),
+        track_number: Some(2),
+        track_total: Some(11),
+        disc_number: Some(1),
+        disc_total: Some(2),
+        disc_subtitle: Some("Side A".to_string()),
+        position: Some("A2".to_string()),
+        position_tag: None,
+        compilation: Some(true),
+    }
+}
+
+/// An album of `tracks`, with a few album-wide tags and no cover.
+#[cfg(test)]
+pub fn album_tags(tracks: &[&str]) -> crate::album_tags::AlbumTags {
+    crate::album_tags::AlbumTags {
+        album_name: Some("Romance".to_string()),
+        artist_name: Some("Fontaines D.C".to_string()),
+        album_artist: None,
+        composer: None,
+        compilation: None,
+        year: Some(2024),
+        genre: vec!["Indie".to_string()],
+        picture_path: None,
+        tracks: tracks.iter().map(|t| t.to_string()).collect(),
+        track_overrides: Vec::new(),
+        discs: Vec::new(),
+        sides: Vec::new(),
+        position_tag: None,
+        disc_total: None,
+        tracks_per_disc: None,
+        track_numbering: crate::album_tags::TrackNumbering::Album,
+    }
+}
+
+/// Write `contents` to `file_name` in a fresh temporary directory for the test `test_name`.
+#[cfg(test)]
+pub fn write_temp(test_name: &str, file_name: &str, contents: &[u8]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("riptagger_{}", test_name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(file_name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Copy a file from `test_data` into a fresh temporary directory, so tests can modify it.
+#[cfg(test)]
+pub fn copy_to_temp(src: &str, test_name: &str) -> std::path::PathBuf {
+    let file_name = std::path::Path::new(src).file_name().unwrap().to_string_lossy();
+    write_temp(test_name, &file_name, &std::fs::read(src).unwrap())
+}
+
+/// Open a file with the built-in backend for `format` and read its tags back.
+#[cfg(test)]
+pub fn read_back(format: crate::audio_format::AudioFormat, path: &std::path::Path) -> crate::track_tags::TrackTags {
+    read_back_with(format, path, None)
+}
+
+/// `read_back`, reading the position from the field `position_tag` names.
+#[cfg(test)]
+pub fn read_back_with(
+    format: crate::audio_format::AudioFormat,
+    path: &std::path::Path,
+    position_tag: Option<&str>,
+) -> crate::track_tags::TrackTags {
+    crate::tag_writer::TagWriterRegistry::default()
+        .open(format, &path.to_string_lossy())
+        .unwrap()
+        .read(position_tag)
+}
@@ -0,0 +1,138 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all};
+use std::path::PathBuf;
+
+use mp4ameta::{ReadConfig, Tag};
+
+use crate::audio_format::AudioFormat;
+use crate::tests::{read_back, sample_tags, write_temp};
+use crate::track_tags::{assign_tags_to_track, TagError, TrackTags};
+
+const AUDIO: &[u8] = b"not really AAC, but the offset has to keep pointing here";
+
+
+fn atom(fourcc: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut bytes = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+    bytes.extend(fourcc);
+    bytes.extend(content);
+    bytes
+}
+
+fn full_atom(fourcc: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut with_version = vec![0u8; 4];
+    with_version.extend(content);
+    atom(fourcc, &with_version)
+}
+
+fn stco(offset: u32) -> Vec<u8> {
+    let mut content = 1u32.to_be_bytes().to_vec();
+    content.extend(offset.to_be_bytes());
+    full_atom(b"stco", &content)
+}
+
+fn moov(chunk_offset: u32) -> Vec<u8> {
+    let mut mvhd = vec![0u8; 96];
+    mvhd[8..12].copy_from_slice(&1000u32.to_be_bytes());
+    let mut tkhd = vec![0u8; 80];
+    tkhd[8..12].copy_from_slice(&1u32.to_be_bytes());
+    let mut mdhd = vec![0u8; 20];
+    mdhd[8..12].copy_from_slice(&44100u32.to_be_bytes());
+    let mut hdlr = vec![0u8; 4];
+    hdlr.extend(b"soun");
+    hdlr.extend([0u8; 13]);
+
+    let stbl = atom(b"stbl", &stco(chunk_offset));
+    let minf = atom(b"minf", &stbl);
+    let mut mdia = full_atom(b"mdhd", &mdhd);
+    mdia.extend(full_atom(b"hdlr", &hdlr));
+    mdia.extend(minf);
+    let mut trak = full_atom(b"tkhd", &tkhd);
+    trak.extend(atom(b"mdia", &mdia));
+    let trak = atom(b"trak", &trak);
+    let mut content = full_atom(b"mvhd", &mvhd);
+    content.extend(trak);
+    atom(b"moov", &content)
+}
+
+/// An ALAC-style layout with `moov` before `mdat`, so tagging moves the audio.
+pub fn m4a_file() -> Vec<u8> {
+    let mut ftyp = b"M4A ".to_vec();
+    ftyp.extend(0u32.to_be_bytes());
+    ftyp.extend(b"M4A mp42isom");
+    let ftyp = atom(b"ftyp", &ftyp);
+
+    let moov_len = moov(0).len();
+    let audio_offset = (ftyp.len() + moov_len + 8) as u32;
+    let mut file = ftyp;
+    file.extend(moov(audio_offset));
+    file.extend(atom(b"mdat", AUDIO));
+    file
+}
+
+fn write_m4a(name: &str) -> PathBuf {
+    write_temp(name, "track.m4a", &m4a_file())
+}
+
+/// Follow the single stco entry and compare it to the audio data.
+fn chunk_offset_is_valid(file: &[u8]) -> bool {
+    let pos = file.windows(4).position(|w| w == b"stco").unwrap();
+    let offset = u32::from_be_bytes(file[pos + 12..pos + 16].try_into().unwrap()) as usize;
+    file[offset..].starts_with(AUDIO)
+}
+
+#[test]
+fn test_assign_mp4_tags() {
+    let path = write_m4a("assign_mp4_tags");
+    let path_str = path.to_string_lossy().to_string();
+    assert!(chunk_offset_is_valid(&read(&path).unwrap()));
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let cfg = ReadConfig { read_audio_info: false, ..ReadConfig::DEFAULT };
+    let tag = Tag::read_with_path(&path, &cfg).unwrap();
+    assert_eq!(tag.title(), Some("Starburster"));
+    assert_eq!(tag.album(), Some("Romance"));
+    assert_eq!(tag.artist(), Some("Fontaines D.C"));
+    assert_eq!(tag.track(), (Some(2), Some(11)));
+    assert_eq!(tag.disc(), (Some(1), Some(2)));
+    assert_eq!(tag.year(), Some("2024"));
+    assert_eq!(tag.genres().collect::<Vec<&str>>(), vec!["Indie", "Post-Punk"]);
+    assert_eq!(tag.artworks().count(), 1);
+    assert!(tag.artwork().unwrap().fmt.is_png());
+
+    assert!(chunk_offset_is_valid(&read(&path).unwrap()));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_mp4_tags_not_mp4() {
+    let path = crate::tests::copy_to_temp("test_data/empty_audio_files/track.m4a", "assign_mp4_tags_not_mp4");
+    let result = assign_tags_to_track(&sample_tags(), &path.to_string_lossy());
+    assert!(result.is_err());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_mp4_tags_number_too_large() {
+    let path = write_m4a("assign_mp4_tags_number_too_large");
+    let tags = TrackTags { track_number: Some(70000), ..sample_tags() };
+
+    let result = assign_tags_to_track(&tags, &path.to_string_lossy());
+    assert!(matches!(result, Err(TagError::TooLarge(_))));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_mp4_tags() {
+    let path = write_m4a("read_back_mp4_tags");
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    let expected = TrackTags { picture_path: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Mp4, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
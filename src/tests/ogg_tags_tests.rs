@@ -0,0 +1,168 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use ogg::{PacketReader, PacketWriteEndInfo, PacketWriter};
+
+use crate::audio_format::AudioFormat;
+use crate::ogg_tags::{read_ogg_comments, OggCodec};
+use crate::tests::{read_back, write_temp};
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+const SERIAL: u32 = 0x1234;
+
+fn sample_tags() -> TrackTags {
+    TrackTags {
+        disc_number: None,
+        disc_total: None,
+        ..crate::tests::sample_tags()
+    }
+}
+
+fn comment_packet(magic: &[u8], comments: &[&str], trailer: &[u8]) -> Vec<u8> {
+    let vendor = b"riptagger test";
+    let mut packet = magic.to_vec();
+    packet.extend((vendor.len() as u32).to_le_bytes());
+    packet.extend(vendor);
+    packet.extend((comments.len() as u32).to_le_bytes());
+    for c in comments {
+        packet.extend((c.len() as u32).to_le_bytes());
+        packet.extend(c.as_bytes());
+    }
+    packet.extend(trailer);
+    packet
+}
+
+/// Build a fake stream: real header packets and filler audio packets,
+/// four to a page, with a granule position per page.
+pub fn ogg_stream(codec: OggCodec) -> Vec<u8> {
+    let mut writer = PacketWriter::new(Vec::new());
+    match codec {
+        OggCodec::Vorbis => {
+            let mut ident = b"\x01vorbis".to_vec();
+            ident.extend([0u8; 23]);
+            writer.write_packet(ident, SERIAL, PacketWriteEndInfo::EndPage, 0).unwrap();
+            let comments = comment_packet(b"\x03vorbis", &["TITLE=Old Title", "COMMENT=keep me"], &[1]);
+            writer.write_packet(comments, SERIAL, PacketWriteEndInfo::NormalPacket, 0).unwrap();
+            let mut setup = b"\x05vorbis".to_vec();
+            setup.extend([0xAAu8; 300]);
+            writer.write_packet(setup, SERIAL, PacketWriteEndInfo::EndPage, 0).unwrap();
+        }
+        OggCodec::Opus => {
+            let mut ident = b"OpusHead".to_vec();
+            ident.extend([1u8, 2, 0x38, 1, 0x80, 0xBB, 0, 0, 0, 0, 0]);
+            writer.write_packet(ident, SERIAL, PacketWriteEndInfo::EndPage, 0).unwrap();
+            let comments = comment_packet(b"OpusTags", &["TITLE=Old Title", "COMMENT=keep me"], &[1, 2, 3]);
+            writer.write_packet(comments, SERIAL, PacketWriteEndInfo::EndPage, 0).unwrap();
+        }
+    }
+    for i in 0..16u64 {
+        let end = if i == 15 {
+            PacketWriteEndInfo::EndStream
+        } else if i % 4 == 3 {
+            PacketWriteEndInfo::EndPage
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(vec![i as u8; 700], SERIAL, end, (i / 4 + 1) * 960).unwrap();
+    }
+
+    writer.into_inner()
+}
+
+fn write_stream(name: &str, codec: OggCodec) -> PathBuf {
+    let file_name = match codec {
+        OggCodec::Vorbis => "track.ogg",
+        OggCodec::Opus => "track.opus",
+    };
+    write_temp(name, file_name, &ogg_stream(codec))
+}
+
+/// (data, granule position, last in page) for every packet after the headers
+fn audio_packets(path: &PathBuf, header_count: usize) -> Vec<(Vec<u8>, u64, bool)> {
+    let mut reader = PacketReader::new(Cursor::new(read(path).unwrap()));
+    let mut packets = Vec::new();
+    while let Some(p) = reader.read_packet().unwrap() {
+        packets.push((p.data.clone(), p.absgp_page(), p.last_in_page()));
+    }
+    packets.split_off(header_count)
+}
+
+#[test]
+fn test_assign_vorbis_comments() {
+    let path = write_stream("assign_vorbis_comments", OggCodec::Vorbis);
+    let path_str = path.to_string_lossy().to_string();
+    let audio_before = audio_packets(&path, 3);
+
+    // enough genres to push the comment header over the 64k page size limit
+    let mut tags = sample_tags();
+    tags.genre.extend((0..300).map(|i| format!("{:0>300}", i)));
+    assign_tags_to_track(&tags, &path_str).unwrap();
+
+    let header = read_ogg_comments(&path_str).unwrap();
+    assert_eq!(header.codec, OggCodec::Vorbis);
+    assert_eq!(header.vendor, "riptagger test");
+    assert_eq!(header.get("TITLE"), vec!["Starburster"]);
+    assert_eq!(header.get("ALBUM"), vec!["Romance"]);
+    assert_eq!(header.get("ARTIST"), vec!["Fontaines D.C"]);
+    assert_eq!(header.get("TRACKNUMBER"), vec!["2"]);
+    assert_eq!(header.get("TRACKTOTAL"), vec!["11"]);
+    assert_eq!(header.get("DATE"), vec!["2024"]);
+    assert_eq!(header.get("GENRE")[..2], ["Indie", "Post-Punk"]);
+    assert_eq!(header.get("GENRE").len(), 302);
+    assert_eq!(header.get("COMMENT"), vec!["keep me"]);
+    assert_eq!(header.get("METADATA_BLOCK_PICTURE").len(), 1);
+
+    // the comment header now spans pages, audio pages must be unchanged
+    assert_eq!(audio_packets(&path, 3), audio_before);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_opus_comments() {
+    let path = write_stream("assign_opus_comments", OggCodec::Opus);
+    let path_str = path.to_string_lossy().to_string();
+    let audio_before = audio_packets(&path, 2);
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let header = read_ogg_comments(&path_str).unwrap();
+    assert_eq!(header.codec, OggCodec::Opus);
+    assert_eq!(header.get("TITLE"), vec!["Starburster"]);
+    assert_eq!(header.get("GENRE"), vec!["Indie", "Post-Punk"]);
+    assert_eq!(header.get("METADATA_BLOCK_PICTURE").len(), 1);
+
+    // binary data after the Opus comment list must survive
+    let mut reader = PacketReader::new(Cursor::new(read(&path).unwrap()));
+    reader.read_packet().unwrap();
+    let packet = reader.read_packet().unwrap().unwrap();
+    assert!(packet.data.ends_with(&[1, 2, 3]));
+
+    assert_eq!(audio_packets(&path, 2), audio_before);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_ogg_tags_not_ogg() {
+    let path = crate::tests::copy_to_temp("test_data/empty_audio_files/track.ogg", "assign_ogg_tags_not_ogg");
+    let result = assign_tags_to_track(&sample_tags(), &path.to_string_lossy());
+    assert!(result.is_err());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_ogg_tags() {
+    let path = write_stream("read_back_ogg_tags", OggCodec::Opus);
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    let expected = TrackTags { picture_path: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Ogg, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
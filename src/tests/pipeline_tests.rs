@@ -0,0 +1,218 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::env::temp_dir;
+use std::fs::{copy, create_dir_all, remove_dir_all};
+use std::path::PathBuf;
+
+use id3::{Tag, TagLike};
+
+use crate::album_tags::{AlbumTags, TrackNumbering};
+use crate::audio_format::AudioFormat;
+use crate::pipeline::{apply, Album, PlanError, PlanOptions};
+use crate::settings::GenreCase;
+use crate::tag_writer::TagWriterRegistry;
+use crate::tests::album_tags;
+
+/// A copy of the three mp3 files in `test_data/test_rename_audio_file`.
+fn album_dir(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("riptagger_{}", name));
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    for file in ["first.mp3", "second.mp3", "third.mp3"] {
+        copy(format!("test_data/test_rename_audio_file/{}", file), dir.join(file)).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_plan() {
+    let dir = album_dir("pipeline_plan");
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+
+    let plans = album.plan(&PlanOptions::default()).unwrap();
+    assert_eq!(plans.len(), 3);
+    // files are matched to tracks in file name order
+    assert_eq!(plans[0].path, dir.join("first.mp3"));
+    assert_eq!(plans[1].path, dir.join("second.mp3"));
+    assert_eq!(plans[2].path, dir.join("third.mp3"));
+    assert_eq!(plans[1].tags.track_name, "Starburster");
+    assert_eq!(plans[1].tags.track_number, Some(2));
+    assert_eq!(plans[1].format, AudioFormat::Mp3);
+    assert_eq!(plans[2].new_name, Some("03 - Heres The Thing.mp3".to_string()));
+    assert_eq!(plans[2].new_path(), dir.join("03 - Heres The Thing.mp3"));
+
+    let options = PlanOptions {
+        rename_files: false,
+        ..PlanOptions::default()
+    };
+    let plans = album.plan(&options).unwrap();
+    assert_eq!(plans[0].new_name, None);
+    assert_eq!(plans[0].new_path(), dir.join("first.mp3"));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_multi_disc_names() {
+    let dir = album_dir("pipeline_plan_multi_disc");
+    let tags = AlbumTags {
+        disc_total: Some(2),
+        tracks_per_disc: Some(vec![2, 1]),
+        track_numbering: TrackNumbering::PerDisc,
+        ..album_tags(&["Romance", "Starburster", "Here's The Thing"])
+    };
+    let album = Album::new(tags, &dir);
+
+    let plans = album.plan(&PlanOptions::default()).unwrap();
+    assert_eq!(plans[0].new_name, Some("1-01 - Romance.mp3".to_string()));
+    assert_eq!(plans[1].new_name, Some("1-02 - Starburster.mp3".to_string()));
+    assert_eq!(plans[2].new_name, Some("2-01 - Heres The Thing.mp3".to_string()));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_finds_cover_picture() {
+    let dir = album_dir("pipeline_plan_cover_picture");
+    copy("test_data/pictures/cover.png", dir.join("folder.png")).unwrap();
+
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+    let plans = album.plan(&PlanOptions::default()).unwrap();
+    let folder = dir.join("folder.png").to_string_lossy().to_string();
+    assert!(plans.iter().all(|p| p.tags.picture_path.as_ref() == Some(&folder)));
+
+    // a configured picture wins
+    let tags = AlbumTags {
+        picture_path: Some("test_data/pictures/cover.png".to_string()),
+        ..album_tags(&["Romance", "Starburster", "Here's The Thing"])
+    };
+    let plans = Album::new(tags, &dir).plan(&PlanOptions::default()).unwrap();
+    assert_eq!(plans[0].tags.picture_path, Some("test_data/pictures/cover.png".to_string()));
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_with_settings() {
+    let dir = album_dir("pipeline_plan_settings");
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+    let options = PlanOptions {
+        file_name_template: Some("{artist} - {album} - {number} {title}".to_string()),
+        genre_case: GenreCase::Upper,
+        ..PlanOptions::default()
+    };
+    let plans = album.plan(&options).unwrap();
+    assert_eq!(plans[2].new_name, Some("Fontaines D.C - Romance - 03 Heres The Thing.mp3".to_string()));
+    assert_eq!(plans[0].tags.genre, vec!["INDIE".to_string()]);
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_picture_too_large() {
+    let dir = album_dir("pipeline_plan_picture_too_large");
+    let tags = AlbumTags {
+        picture_path: Some("test_data/pictures/cover.png".to_string()),
+        ..album_tags(&["Romance", "Starburster", "Here's The Thing"])
+    };
+    let album = Album::new(tags, &dir);
+    let options = PlanOptions {
+        max_picture_size: Some(10),
+        ..PlanOptions::default()
+    };
+    match album.plan(&options) {
+        Err(PlanError::PictureTooLarge { path, limit, .. }) => {
+            assert_eq!(path, "test_data/pictures/cover.png");
+            assert_eq!(limit, 10);
+        }
+        other => panic!("expected the picture to be too large, got {:?}", other),
+    }
+
+    let options = PlanOptions {
+        max_picture_size: Some(10_000_000),
+        ..PlanOptions::default()
+    };
+    assert!(album.plan(&options).is_ok());
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_name_taken() {
+    let dir = album_dir("pipeline_plan_name_taken");
+    // not an audio file, so not one of the tracks
+    create_dir_all(dir.join("02 - Starburster.mp3")).unwrap();
+
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+    match album.plan(&PlanOptions::default()) {
+        Err(PlanError::NameTaken { path, new_path }) => {
+            assert_eq!(path, dir.join("second.mp3"));
+            assert_eq!(new_path, dir.join("02 - Starburster.mp3"));
+        }
+        other => panic!("expected the name to be taken, got {:?}", other),
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_duplicate_name() {
+    let dir = album_dir("pipeline_plan_duplicate_name");
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+    let options = PlanOptions {
+        file_name_template: Some("{album}".to_string()),
+        ..PlanOptions::default()
+    };
+    match album.plan(&options) {
+        Err(PlanError::DuplicateName { first, second, new_path }) => {
+            assert_eq!(first, dir.join("first.mp3"));
+            assert_eq!(second, dir.join("second.mp3"));
+            assert_eq!(new_path, dir.join("Romance.mp3"));
+        }
+        other => panic!("expected a duplicate name, got {:?}", other),
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_track_count_mismatch() {
+    let dir = album_dir("pipeline_track_count_mismatch");
+    let album = Album::new(album_tags(&["Romance", "Starburster"]), &dir);
+
+    match album.plan(&PlanOptions::default()) {
+        Err(PlanError::TrackCountMismatch { configured, found }) => {
+            assert_eq!(configured, 2);
+            assert_eq!(found.len(), 3);
+        }
+        other => panic!("expected a track count mismatch, got {:?}", other),
+    }
+
+    remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plan_missing_directory() {
+    let album = Album::new(album_tags(&["Romance"]), "test_data/no_such_directory");
+    assert!(matches!(album.plan(&PlanOptions::default()), Err(PlanError::Discovery(_))));
+}
+
+#[test]
+fn test_apply() {
+    let dir = album_dir("pipeline_apply");
+    let album = Album::new(album_tags(&["Romance", "Starburster", "Here's The Thing"]), &dir);
+    let plans = album.plan(&PlanOptions::default()).unwrap();
+
+    let results = apply(&plans, &TagWriterRegistry::default());
+    let new_paths: Vec<PathBuf> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(new_paths[0], dir.join("01 - Romance.mp3"));
+    assert!(!dir.join("first.mp3").exists());
+
+    let tag = Tag::read_from_path(&new_paths[1]).unwrap();
+    assert_eq!(tag.title(), Some("Starburster"));
+    assert_eq!(tag.album(), Some("Romance"));
+    assert_eq!(tag.track(), Some(2));
+    assert_eq!(tag.total_tracks(), Some(3));
+
+    remove_dir_all(dir).unwrap();
+}
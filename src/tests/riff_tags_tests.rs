@@ -0,0 +1,142 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::fs::{read, remove_dir_all};
+
+use id3::{TagLike, Version};
+
+use crate::audio_format::AudioFormat;
+use crate::riff_tags::{read_wav_chunks, read_wav_id3, read_wav_info, WavWriter};
+use crate::tag_writer::TagWriter;
+use crate::tests::{read_back, write_temp};
+use crate::track_tags::{assign_tags_to_track, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags { disc_total: Some(1), ..crate::tests::sample_tags() }
+}
+
+/// fmt and data chunks, plus an existing INFO list with a field riptagger doesn't set.
+pub fn wav_file() -> Vec<u8> {
+    let mut body = b"WAVE".to_vec();
+    body.extend(b"fmt ");
+    body.extend(16u32.to_le_bytes());
+    body.extend([1, 0, 2, 0, 0x44, 0xAC, 0, 0, 0x10, 0xB1, 2, 0, 4, 0, 16, 0]);
+    body.extend(b"data");
+    body.extend(7u32.to_le_bytes());
+    body.extend([9u8; 7]);
+    body.push(0);
+    body.extend(b"LIST");
+    body.extend(18u32.to_le_bytes());
+    body.extend(b"INFOISFT");
+    body.extend(6u32.to_le_bytes());
+    body.extend(b"rip42\0");
+
+    let mut file = b"RIFF".to_vec();
+    file.extend((body.len() as u32).to_le_bytes());
+    file.extend(body);
+    file
+}
+
+fn aiff_file() -> Vec<u8> {
+    let mut body = b"AIFF".to_vec();
+    body.extend(b"COMM");
+    body.extend(18u32.to_be_bytes());
+    body.extend([0u8; 18]);
+    body.extend(b"SSND");
+    body.extend(16u32.to_be_bytes());
+    body.extend([7u8; 16]);
+
+    let mut file = b"FORM".to_vec();
+    file.extend((body.len() as u32).to_be_bytes());
+    file.extend(body);
+    file
+}
+
+#[test]
+fn test_assign_wav_tags() {
+    let path = write_temp("assign_wav_tags", "track.wav", &wav_file());
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let info = read_wav_info(&path_str).unwrap();
+    let field = |id: &[u8; 4]| info.iter().find(|(i, _)| i == id).map(|(_, v)| v.as_str());
+    assert_eq!(field(b"INAM"), Some("Starburster"));
+    assert_eq!(field(b"IART"), Some("Fontaines D.C"));
+    assert_eq!(field(b"IPRD"), Some("Romance"));
+    assert_eq!(field(b"ITRK"), Some("2"));
+    assert_eq!(field(b"ICRD"), Some("2024"));
+    assert_eq!(field(b"IGNR"), Some("Indie; Post-Punk"));
+    assert_eq!(field(b"ISFT"), Some("rip42"));
+
+    let tag = read_wav_id3(&path_str).unwrap();
+    assert_eq!(tag.title(), Some("Starburster"));
+    assert_eq!(tag.album(), Some("Romance"));
+    assert_eq!(tag.total_tracks(), Some(11));
+    assert_eq!(tag.pictures().count(), 1);
+
+    let file = read(&path).unwrap();
+    let chunks = read_wav_chunks(&file).unwrap();
+    let ids: Vec<&[u8; 4]> = chunks.iter().map(|c| &c.id).collect();
+    assert_eq!(ids, vec![b"fmt ", b"data", b"LIST", b"id3 "]);
+    assert_eq!(chunks[1].data, vec![9u8; 7]);
+    assert_eq!(u32::from_le_bytes(file[4..8].try_into().unwrap()) as usize, file.len() - 8);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_write_wav_id3v23_tags() {
+    let path = write_temp("write_wav_id3v23_tags", "track.wav", &wav_file());
+    let writer = WavWriter { version: Version::Id3v23 };
+    let mut file = writer.open(&path.to_string_lossy()).unwrap();
+    file.apply(&sample_tags()).unwrap();
+    file.save().unwrap();
+
+    let tag = read_wav_id3(&path.to_string_lossy()).unwrap();
+    assert_eq!(tag.version(), Version::Id3v23);
+    assert_eq!(tag.year(), Some(2024));
+    assert!(tag.get("TDRC").is_none());
+    assert_eq!(read_back(AudioFormat::Wav, &path).year, Some(2024));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_aiff_tags() {
+    let path = write_temp("assign_aiff_tags", "track.aiff", &aiff_file());
+    let path_str = path.to_string_lossy().to_string();
+
+    assign_tags_to_track(&sample_tags(), &path_str).unwrap();
+
+    let tag = id3::Tag::read_from_path(&path).unwrap();
+    assert_eq!(tag.title(), Some("Starburster"));
+    assert_eq!(tag.artist(), Some("Fontaines D.C"));
+    assert_eq!(tag.disc(), Some(1));
+
+    let file = read(&path).unwrap();
+    assert_eq!(&file[0..4], b"FORM");
+    assert!(file.windows(4).any(|w| w == b"ID3 "));
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_assign_wav_tags_not_wav() {
+    let path = write_temp("assign_wav_tags_not_wav", "track.wav", b"not a wave file");
+    let result = assign_tags_to_track(&sample_tags(), &path.to_string_lossy());
+    assert!(result.is_err());
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_read_back_wav_tags() {
+    let path = write_temp("read_back_wav_tags", "track.wav", &wav_file());
+    assign_tags_to_track(&sample_tags(), &path.to_string_lossy()).unwrap();
+
+    let expected = TrackTags { picture_path: None, ..sample_tags() };
+    assert_eq!(read_back(AudioFormat::Wav, &path), expected);
+
+    remove_dir_all(path.parent().unwrap()).unwrap();
+}
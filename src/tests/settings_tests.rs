@@ -0,0 +1,98 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::path::Path;
+
+use crate::config::{ConfigError, ConfigFormat};
+use crate::config_chain::resolve_config;
+use crate::settings::{load_user_settings, GenreCase, Id3Version, Settings, SettingsConfig};
+
+#[test]
+fn test_genre_case() {
+    assert_eq!(GenreCase::AsWritten.apply("post-punk"), "post-punk");
+    assert_eq!(GenreCase::Title.apply("post-punk"), "Post-Punk");
+    assert_eq!(GenreCase::Title.apply("drum and bass"), "Drum And Bass");
+    assert_eq!(GenreCase::Lower.apply("Indie"), "indie");
+    assert_eq!(GenreCase::Upper.apply("Indie"), "INDIE");
+}
+
+#[test]
+fn test_load_user_settings() {
+    let settings = load_user_settings(Path::new("test_data/configs/settings/config.toml")).unwrap();
+    assert_eq!(
+        settings,
+        SettingsConfig {
+            rename_files: Some(false),
+            genre_case: Some(GenreCase::Title),
+            id3_version: Some(Id3Version::V23),
+            ..SettingsConfig::default()
+        }
+    );
+
+    // no user config is the same as an empty one
+    let settings = load_user_settings(Path::new("test_data/configs/settings/missing.toml")).unwrap();
+    assert_eq!(settings, SettingsConfig::default());
+}
+
+#[test]
+fn test_load_user_settings_unknown_key() {
+    match load_user_settings(Path::new("test_data/configs/settings/bad_config.toml")) {
+        Err(ConfigError::InFile { path, diagnostics, error }) => {
+            assert_eq!(path, "test_data/configs/settings/bad_config.toml");
+            assert_eq!(diagnostics[0].line, Some(2));
+            assert!(matches!(*error, ConfigError::UnknownKey { suggestion: Some(ref s), .. } if s == "genre_case"));
+        }
+        other => panic!("expected an unknown key, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_settings_from_album_config() {
+    let resolved = resolve_config("test_data/configs/settings/album.toml", ConfigFormat::Toml).unwrap();
+    let (source, settings) = SettingsConfig::from_album_config(&resolved).unwrap().unwrap();
+    assert_eq!(source, "test_data/configs/settings/album.toml");
+    assert_eq!(settings.genre_case, Some(GenreCase::Upper));
+
+    let resolved = resolve_config("test_data/configs/valid_config.toml", ConfigFormat::Toml).unwrap();
+    assert_eq!(SettingsConfig::from_album_config(&resolved).unwrap(), None);
+}
+
+#[test]
+fn test_settings_from_layers() {
+    let user = load_user_settings(Path::new("test_data/configs/settings/config.toml")).unwrap();
+    let album = SettingsConfig {
+        genre_case: Some(GenreCase::Upper),
+        max_picture_size: Some(500_000),
+        ..SettingsConfig::default()
+    };
+    let command_line = SettingsConfig {
+        rename_files: Some(true),
+        ..SettingsConfig::default()
+    };
+    let settings = Settings::from_layers(&[("config.toml", &user), ("album.toml", &album), ("command line", &command_line)]);
+
+    assert!(settings.rename_files);
+    assert_eq!(settings.genre_case, GenreCase::Upper);
+    assert_eq!(settings.max_picture_size, Some(500_000));
+    assert_eq!(settings.id3_version, Id3Version::V23);
+    assert_eq!(settings.file_name_template, None);
+    assert_eq!(settings.sources.get("rename_files").map(String::as_str), Some("command line"));
+    assert_eq!(settings.sources.get("genre_case").map(String::as_str), Some("album.toml"));
+    assert_eq!(settings.sources.get("id3_version").map(String::as_str), Some("config.toml"));
+    assert_eq!(settings.sources.get("file_name_template"), None);
+
+    assert_eq!(Settings::from_layers(&[]), Settings::default());
+}
+
+#[test]
+fn test_show_settings() {
+    let user = load_user_settings(Path::new("test_data/configs/settings/config.toml")).unwrap();
+    let settings = Settings::from_layers(&[("config.toml", &user)]);
+    assert_eq!(
+        settings.to_string(),
+        "rename_files = false  # config.toml\n\
+         # file_name_template is not set\n\
+         genre_case = \"title\"  # config.toml\n\
+         # max_picture_size is not set\n\
+         id3_version = \"2.3\"  # config.toml\n"
+    );
+}
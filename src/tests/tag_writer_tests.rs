@@ -0,0 +1,170 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{read, remove_dir_all};
+use std::rc::Rc;
+
+use crate::audio_format::AudioFormat;
+use crate::ogg_tags::OggCodec;
+use crate::tag_writer::{parse_number_of, parse_year, TagWriter, TagWriterRegistry, TrackFile};
+use crate::tests::ape_tags_tests::wavpack_file;
+use crate::tests::asf_tags_tests::asf_file;
+use crate::tests::mp4_tags_tests::m4a_file;
+use crate::tests::ogg_tags_tests::ogg_stream;
+use crate::tests::riff_tags_tests::wav_file;
+use crate::tests::{read_back, read_back_with, write_temp};
+use crate::track_tags::{assign_tags_to_track, TagError, TrackTags};
+
+fn sample_tags() -> TrackTags {
+    TrackTags {
+        album_artist: None,
+        composer: None,
+        genre: vec!["Indie".to_string()],
+        picture_path: None,
+        disc_number: None,
+        disc_total: None,
+        disc_subtitle: None,
+        position: None,
+        compilation: None,
+        ..crate::tests::sample_tags()
+    }
+}
+
+/// Saved tags, keyed by path.
+type Store = Rc<RefCell<HashMap<String, TrackTags>>>;
+
+/// A backend that keeps tags in memory instead of writing files.
+struct MemoryWriter {
+    store: Store,
+}
+
+struct MemoryFile {
+    path: String,
+    tags: TrackTags,
+    store: Store,
+}
+
+impl TagWriter for MemoryWriter {
+    fn open(&self, track_path: &str) -> Result<Box<dyn TrackFile>, TagError> {
+        let tags = self.store.borrow().get(track_path).cloned().unwrap_or_default();
+        Ok(Box::new(MemoryFile {
+            path: track_path.to_string(),
+            tags,
+            store: self.store.clone(),
+        }))
+    }
+}
+
+impl TrackFile for MemoryFile {
+    fn read(&self, _position_tag: Option<&str>) -> TrackTags {
+        self.tags.clone()
+    }
+
+    fn apply(&mut self, tags: &TrackTags) -> Result<(), TagError> {
+        self.tags = tags.clone();
+        Ok(())
+    }
+
+    fn save(self: Box<Self>) -> Result<(), TagError> {
+        self.store.borrow_mut().insert(self.path, self.tags);
+        Ok(())
+    }
+}
+
+fn memory_registry(format: AudioFormat) -> (TagWriterRegistry, Store) {
+    let store = Store::default();
+    let mut registry = TagWriterRegistry::new();
+    registry.register(format, MemoryWriter { store: store.clone() });
+    (registry, store)
+}
+
+#[test]
+fn test_registry_routes_by_format() {
+    let (registry, store) = memory_registry(AudioFormat::Flac);
+    let path = "test_data/empty_audio_files/track.flac";
+
+    registry.assign(&sample_tags(), path).unwrap();
+
+    assert_eq!(store.borrow().get(path), Some(&sample_tags()));
+    assert_eq!(registry.open(AudioFormat::Flac, path).unwrap().read(None), sample_tags());
+}
+
+#[test]
+fn test_registry_unregistered_format() {
+    let (registry, store) = memory_registry(AudioFormat::Flac);
+
+    let result = registry.assign(&sample_tags(), "test_data/empty_audio_files/track.mp3");
+    assert!(matches!(result, Err(TagError::UnsupportedFormat(_))));
+    assert!(store.borrow().is_empty());
+}
+
+#[test]
+fn test_nothing_written_without_save() {
+    let (registry, store) = memory_registry(AudioFormat::Mp3);
+
+    let mut file = registry.open(AudioFormat::Mp3, "track.mp3").unwrap();
+    file.apply(&sample_tags()).unwrap();
+    assert_eq!(file.read(None), sample_tags());
+    assert!(store.borrow().is_empty());
+}
+
+#[test]
+fn test_default_registry_has_every_format() {
+    let registry = TagWriterRegistry::default();
+    for format in [
+        AudioFormat::Flac,
+        AudioFormat::Mp3,
+        AudioFormat::Ogg,
+        AudioFormat::Asf,
+        AudioFormat::Mp4,
+        AudioFormat::Ape,
+        AudioFormat::Wav,
+        AudioFormat::Aiff,
+    ] {
+        assert!(registry.get(format).is_some(), "no backend for {}", format);
+    }
+}
+
+#[test]
+fn test_read_back_custom_position_tag() {
+    let fixtures = [
+        (AudioFormat::Mp3, "track.mp3", read("test_data/empty_audio_files/track.mp3").unwrap()),
+        (AudioFormat::Ape, "track.wv", wavpack_file()),
+        (AudioFormat::Asf, "track.wma", asf_file(Vec::new())),
+        (AudioFormat::Mp4, "track.m4a", m4a_file()),
+        (AudioFormat::Ogg, "track.opus", ogg_stream(OggCodec::Opus)),
+        (AudioFormat::Wav, "track.wav", wav_file()),
+    ];
+    let tags = TrackTags {
+        position: Some("A2".to_string()),
+        position_tag: Some("DISCOGS_POSITION".to_string()),
+        ..sample_tags()
+    };
+    for (format, file_name, contents) in fixtures {
+        let path = write_temp(&format!("read_back_{}_custom_position_tag", format), file_name, &contents);
+        assign_tags_to_track(&tags, &path.to_string_lossy()).unwrap();
+
+        let read = read_back_with(format, &path, Some("DISCOGS_POSITION"));
+        assert_eq!(read.position, tags.position, "{}", format);
+        assert_eq!(read.position_tag, tags.position_tag, "{}", format);
+        assert_eq!(read_back(format, &path).position, None, "{}", format);
+
+        remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn test_parse_number_of() {
+    assert_eq!(parse_number_of("3/12"), (Some(3), Some(12)));
+    assert_eq!(parse_number_of("3"), (Some(3), None));
+    assert_eq!(parse_number_of(" 3 / 12 "), (Some(3), Some(12)));
+    assert_eq!(parse_number_of("three"), (None, None));
+}
+
+#[test]
+fn test_parse_year() {
+    assert_eq!(parse_year("2024"), Some(2024));
+    assert_eq!(parse_year("2024-03-01"), Some(2024));
+    assert_eq!(parse_year("24"), None);
+}
@@ -0,0 +1,244 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+use std::error::Error;
+
+use toml::Table;
+
+use crate::album_tags::AlbumTags;
+use crate::config::ConfigError;
+use crate::validation::{suggest, validate_album_table, validate_settings_table};
+
+#[test]
+fn test_valid_table() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        album = "Romance"
+        artist = "Fontaines D.C"
+        year = 2024
+        genre = ["Indie", "Post-Punk"]
+        compilation = false
+        tracks = ["Romance", "Starburster", "Here's The Thing"]
+        disc_total = 2
+        tracks_per_disc = [2, 1]
+    "#)?;
+    validate_album_table(&table)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_key_suggestion() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        artsit = "Fontaines D.C"
+        tracks = ["Romance"]
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(ConfigError::UnknownKey { key, suggestion }) => {
+            assert_eq!(key, "artsit");
+            assert_eq!(suggestion, Some("artist".to_string()));
+        }
+        other => panic!("expected an unknown key, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_key_without_suggestion() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["Romance"]
+        producer = "Dan Carey"
+    "#)?;
+    let err = validate_album_table(&table).unwrap_err();
+    assert_eq!(format!("{}", err), "Unknown key: producer");
+
+    Ok(())
+}
+
+#[test]
+fn test_nested_unknown_key() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        [[disc]]
+        subtitel = "Early Show"
+        tracks = ["Intro"]
+    "#)?;
+    let err = validate_album_table(&table).unwrap_err();
+    assert_eq!(format!("{}", err), "Unknown key: disc[0].subtitel (did you mean 'disc[0].subtitle'?)");
+
+    Ok(())
+}
+
+#[test]
+fn test_wrong_type() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        year = "2024"
+        tracks = ["Romance"]
+    "#)?;
+    match AlbumTags::from_toml(table) {
+        Err(ConfigError::TypeError { key, message }) => {
+            assert_eq!(key, "year");
+            assert_eq!(message, "year should be an integer");
+        }
+        other => panic!("expected a type error, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_inconsistent_discs() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["One", "Two", "Three"]
+        disc_total = 3
+        tracks_per_disc = [1, 1]
+    "#)?;
+    match validate_album_table(&table) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2);
+            assert!(problems.iter().all(|p| matches!(p, ConfigError::Inconsistent { .. })));
+        }
+        other => panic!("expected two problems, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_inconsistent_discs_track_tables() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks_per_disc = [1, 1]
+
+        [[track]]
+        title = "Romance"
+
+        [[track]]
+        title = "Starburster"
+
+        [[track]]
+        title = "Here's The Thing"
+    "#)?;
+    let err = validate_album_table(&table).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "Inconsistent config: tracks_per_disc adds up to 2 tracks, but [[track]] lists 3"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_several_problems() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        albun = "Romance"
+        year = "2024"
+        tracks = ["Romance"]
+    "#)?;
+    let err = validate_album_table(&table).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "2 problems in config:\n  Unknown key: albun (did you mean 'album'?)\n  Type error: year should be an integer"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_types_described_from_schema() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        genre = 1
+        tracks_per_disc = ["one"]
+
+        [[disc]]
+        tracks = "Romance"
+
+        [settings]
+        id3_version = "2.2"
+    "#)?;
+    match validate_album_table(&table) {
+        Err(ConfigError::Invalid(problems)) => {
+            let messages: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+            assert_eq!(
+                messages,
+                vec![
+                    "Type error: disc[0].tracks should be an array of strings",
+                    "Type error: genre should be a string or an array of strings",
+                    "Type error: settings.id3_version should be one of \"2.3\", \"2.4\"",
+                    "Type error: tracks_per_disc should be an array of integers",
+                ]
+            );
+        }
+        other => panic!("expected four problems, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_suggest() {
+    let keys = ["genre", "tracks", "disc_total", "tracks_per_disc"];
+    assert_eq!(suggest("genres", keys), Some("genre"));
+    assert_eq!(suggest("track_total", keys), None);
+    assert_eq!(suggest("disk_total", keys), Some("disc_total"));
+}
+
+#[test]
+fn test_one_of() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["Romance"]
+        track_numbering = "sideways"
+    "#)?;
+    match validate_album_table(&table) {
+        Err(ConfigError::TypeError { message, .. }) => {
+            assert_eq!(message, "track_numbering should be one of \"album\", \"per_disc\"")
+        }
+        other => panic!("expected a type error, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_settings_table() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["Romance"]
+
+        [settings]
+        genre_case = "shouting"
+        max_picture_size = "big"
+    "#)?;
+    match validate_album_table(&table) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2);
+            assert_eq!(problems[0].key_path(), Some("settings.genre_case".to_string()));
+            assert_eq!(problems[1].key_path(), Some("settings.max_picture_size".to_string()));
+        }
+        other => panic!("expected two problems, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_file_name_template() -> Result<(), Box<dyn Error>> {
+    let table: Table = toml::from_str(r#"
+        tracks = ["Romance"]
+
+        [settings]
+        file_name_template = "{artist}/{album}"
+    "#)?;
+    match validate_album_table(&table) {
+        Err(ConfigError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2);
+            assert!(problems[0].to_string().contains("should contain {number}, {track}, {position}"));
+            assert!(problems[1].to_string().contains("can't contain a path separator"));
+            assert_eq!(problems[1].key_path(), Some("settings.file_name_template".to_string()));
+        }
+        other => panic!("expected two problems, got {:?}", other),
+    }
+
+    let table: Table = toml::from_str(r#"
+        file_name_template = "{artist} - {track} {title}"
+    "#)?;
+    validate_settings_table(&table)?;
+
+    Ok(())
+}
@@ -2,15 +2,91 @@
 // GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
 
 use std::fmt;
-use std::fs::read;
+use std::io;
 
-use metaflac::block::PictureType::CoverFront;
-use metaflac::{Error, Tag};
+use crate::tag_writer::{parse_flag, parse_number_of, parse_year, TagWriterRegistry};
 
 #[derive(Debug)]
+pub enum TagError {
+    Io(io::Error),
+    Flac(metaflac::Error),
+    Id3(id3::Error),
+    Ogg(ogg::OggReadError),
+    Mp4(mp4ameta::Error),
+    Ape(ape::Error),
+    /// The cover picture at the given path couldn't be read.
+    Picture(String, io::Error),
+    InvalidFile(String),
+    UnsupportedFormat(String),
+    /// A value doesn't fit in the field the format stores it in.
+    TooLarge(String),
+}
+
+impl From<io::Error> for TagError {
+    fn from(e: io::Error) -> Self {
+        TagError::Io(e)
+    }
+}
+
+impl From<metaflac::Error> for TagError {
+    fn from(e: metaflac::Error) -> Self {
+        TagError::Flac(e)
+    }
+}
+
+impl From<id3::Error> for TagError {
+    fn from(e: id3::Error) -> Self {
+        TagError::Id3(e)
+    }
+}
+
+impl From<ogg::OggReadError> for TagError {
+    fn from(e: ogg::OggReadError) -> Self {
+        TagError::Ogg(e)
+    }
+}
+
+impl From<mp4ameta::Error> for TagError {
+    fn from(e: mp4ameta::Error) -> Self {
+        TagError::Mp4(e)
+    }
+}
+
+impl From<ape::Error> for TagError {
+    fn from(e: ape::Error) -> Self {
+        TagError::Ape(e)
+    }
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TagError::*;
+        match self {
+            Io(e) => write!(f, "IO error: {}", e),
+            Flac(e) => write!(f, "FLAC error: {}", e),
+            Id3(e) => write!(f, "ID3 error: {}", e),
+            Ogg(e) => write!(f, "Ogg error: {}", e),
+            Mp4(e) => write!(f, "MP4 error: {}", e),
+            Ape(e) => write!(f, "APE error: {}", e),
+            Picture(path, e) => write!(f, "Could not read picture '{}': {}", path, e),
+            InvalidFile(msg) => write!(f, "Invalid file: {}", msg),
+            UnsupportedFormat(ext) => write!(f, "Unsupported format: {}", ext),
+            TooLarge(msg) => write!(f, "Value too large: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+/// Field holding a track's side position, such as "A1", unless the config names another.
+pub const DEFAULT_POSITION_TAG: &str = "VINYLTRACK";
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct TrackTags {
     pub album_name: Option<String>,
     pub artist_name: Option<String>,
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
     pub year: Option<i64>,
     pub track_name: String,
     pub genre: Vec<String>,
@@ -19,78 +95,174 @@ pub struct TrackTags {
     pub track_total: Option<i64>,
     pub disc_number: Option<i64>,
     pub disc_total: Option<i64>,
+    pub disc_subtitle: Option<String>,
+    /// Side position on vinyl, such as "A1".
+    pub position: Option<String>,
+    /// Field `position` is written to, `DEFAULT_POSITION_TAG` when `None`.
+    pub position_tag: Option<String>,
+    /// `None` leaves the file's compilation flag alone, `Some(false)` clears it.
+    pub compilation: Option<bool>,
+}
+
+impl TrackTags {
+    /// The field name `position` is written to.
+    pub fn position_key(&self) -> &str {
+        self.position_tag.as_deref().unwrap_or(DEFAULT_POSITION_TAG)
+    }
 }
 
 impl fmt::Display for TrackTags {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "album_name: {:?}\n", self.album_name)
-            .and_then(|_| write!(f, "artist_name: {:?}\n", self.artist_name))
-            .and_then(|_| write!(f, "year: {:?}\n", self.year.map(|s| s.to_string())))
-            .and_then(|_| write!(f, "track_name: {:?}\n", self.track_name))
-            .and_then(|_| write!(f, "genre: {:?}\n", self.genre))
-            .and_then(|_| write!(f, "track_number: {:?}\n", self.track_number))
-            .and_then(|_| write!(f, "track_total: {:?}\n", self.track_total))
+        writeln!(f, "album_name: {:?}", self.album_name)
+            .and_then(|_| writeln!(f, "artist_name: {:?}", self.artist_name))
+            .and_then(|_| writeln!(f, "album_artist: {:?}", self.album_artist))
+            .and_then(|_| writeln!(f, "composer: {:?}", self.composer))
+            .and_then(|_| writeln!(f, "year: {:?}", self.year.map(|s| s.to_string())))
+            .and_then(|_| writeln!(f, "track_name: {:?}", self.track_name))
+            .and_then(|_| writeln!(f, "genre: {:?}", self.genre))
+            .and_then(|_| writeln!(f, "track_number: {:?}", self.track_number))
+            .and_then(|_| writeln!(f, "track_total: {:?}", self.track_total))
             .and_then(|_| {
-                write!(
+                writeln!(
                     f,
-                    "disc_number: {:?}\n",
+                    "disc_number: {:?}",
                     self.disc_number.map(|x| x.to_string())
                 )
             })
             .and_then(|_| {
-                write!(
+                writeln!(
                     f,
                     "disc_total: {:?}",
                     self.disc_total.map(|x| x.to_string())
                 )
             })
+            .and_then(|_| writeln!(f, "disc_subtitle: {:?}", self.disc_subtitle))
+            .and_then(|_| writeln!(f, "position: {:?}", self.position))
+            .and_then(|_| write!(f, "compilation: {:?}", self.compilation))
     }
 }
 
-pub fn assign_tags_to_track(tags: &TrackTags, track_path: &str) -> Result<(), Error> {
-    let mut file = match Tag::read_from_path(track_path) {
-        Ok(f) => f,
-        Err(e) => return Err(e),
-    };
+/// Guess the MIME type of an image from its leading bytes, falling back to JPEG.
+pub fn picture_mime_type(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if data.starts_with(b"GIF8") {
+        "image/gif"
+    } else {
+        "image/jpeg"
+    }
+}
+
+/// Tag a track with the built-in backends, routing it by its contents rather than its extension.
+pub fn assign_tags_to_track(tags: &TrackTags, track_path: &str) -> Result<(), TagError> {
+    TagWriterRegistry::default().assign(tags, track_path)
+}
+
+/// Vorbis comment fields for `tags`, shared by the FLAC and Ogg backends.
+/// The cover picture is not included, each container stores it differently.
+pub fn vorbis_comments(tags: &TrackTags) -> Vec<(String, Vec<String>)> {
+    let mut comments = Vec::new();
 
     if let Some(album_name) = &tags.album_name {
-        file.set_vorbis("ALBUM", vec![album_name]);
+        comments.push(("ALBUM", vec![album_name.clone()]));
     }
 
     if let Some(artist_name) = &tags.artist_name {
-        file.set_vorbis("ARTIST", vec![artist_name]);
+        comments.push(("ARTIST", vec![artist_name.clone()]));
     }
 
-    file.set_vorbis("TITLE", vec![&tags.track_name]);
+    if let Some(album_artist) = &tags.album_artist {
+        comments.push(("ALBUMARTIST", vec![album_artist.clone()]));
+    }
+
+    if let Some(composer) = &tags.composer {
+        comments.push(("COMPOSER", vec![composer.clone()]));
+    }
+
+    comments.push(("TITLE", vec![tags.track_name.clone()]));
 
     if let Some(track_number) = &tags.track_number {
-        file.set_vorbis("TRACKNUMBER", vec![track_number.to_string()]);
+        comments.push(("TRACKNUMBER", vec![track_number.to_string()]));
     }
 
     if let Some(track_total) = &tags.track_total {
-        file.set_vorbis("TRACKTOTAL", vec![track_total.to_string()]);
+        comments.push(("TRACKTOTAL", vec![track_total.to_string()]));
     }
 
     if let Some(year) = tags.year {
-        file.set_vorbis("DATE", vec![year.to_string()]);
+        comments.push(("DATE", vec![year.to_string()]));
     }
 
     if !&tags.genre.is_empty() {
-        file.set_vorbis("GENRE", tags.genre.clone());
+        comments.push(("GENRE", tags.genre.clone()));
     }
 
     if let Some(disc_num) = &tags.disc_number {
-        file.set_vorbis("DISCNUMBER", vec![disc_num.to_string()]);
+        comments.push(("DISCNUMBER", vec![disc_num.to_string()]));
     }
 
     if let Some(disc_total) = &tags.disc_total {
-        file.set_vorbis("TOTALDISCS", vec![disc_total.to_string()]);
+        comments.push(("TOTALDISCS", vec![disc_total.to_string()]));
     }
 
-    if let Some(cover_path) = &tags.picture_path {
-        let pic = read(cover_path).unwrap();
-        file.add_picture("image/jpeg", CoverFront, pic);
+    if let Some(disc_subtitle) = &tags.disc_subtitle {
+        comments.push(("DISCSUBTITLE", vec![disc_subtitle.clone()]));
     }
 
-    file.save()
+    // setting no values removes the field
+    match tags.compilation {
+        Some(true) => comments.push(("COMPILATION", vec![String::from("1")])),
+        Some(false) => comments.push(("COMPILATION", vec![])),
+        None => (),
+    }
+
+    let mut comments: Vec<(String, Vec<String>)> = comments
+        .into_iter()
+        .map(|(key, values)| (key.to_string(), values))
+        .collect();
+    if let Some(position) = &tags.position {
+        comments.push((tags.position_key().to_ascii_uppercase(), vec![position.clone()]));
+    }
+
+    comments
+}
+
+/// Map Vorbis comments back onto `TrackTags`. `get` returns all values of a field.
+pub fn track_tags_from_vorbis<F>(get: F, position_tag: Option<&str>) -> TrackTags
+where
+    F: Fn(&str) -> Vec<String>,
+{
+    let first = |key: &str| get(key).into_iter().next();
+    // TRACKNUMBER is sometimes written as "n/m"
+    let (track_number, track_in_number) = first("TRACKNUMBER")
+        .map(|v| parse_number_of(&v))
+        .unwrap_or_default();
+    let (disc_number, disc_in_number) = first("DISCNUMBER")
+        .map(|v| parse_number_of(&v))
+        .unwrap_or_default();
+
+    TrackTags {
+        album_name: first("ALBUM"),
+        artist_name: first("ARTIST"),
+        album_artist: first("ALBUMARTIST"),
+        composer: first("COMPOSER"),
+        year: first("DATE").and_then(|v| parse_year(&v)),
+        track_name: first("TITLE").unwrap_or_default(),
+        genre: get("GENRE"),
+        picture_path: None,
+        track_number,
+        track_total: first("TRACKTOTAL")
+            .or_else(|| first("TOTALTRACKS"))
+            .and_then(|v| v.trim().parse().ok())
+            .or(track_in_number),
+        disc_number,
+        disc_total: first("TOTALDISCS")
+            .or_else(|| first("DISCTOTAL"))
+            .and_then(|v| v.trim().parse().ok())
+            .or(disc_in_number),
+        disc_subtitle: first("DISCSUBTITLE"),
+        position: first(&position_tag.unwrap_or(DEFAULT_POSITION_TAG).to_ascii_uppercase()),
+        position_tag: position_tag.map(String::from),
+        compilation: first("COMPILATION").map(|v| parse_flag(&v)),
+    }
 }
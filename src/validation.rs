@@ -0,0 +1,258 @@
+// Copyright: (c) 2025, Colm Murphy
+// GNU General Public License v3.0 (see COPYING or https://www.gnu.org/licenses/gpl-3.0.txt)
+
+use std::sync::LazyLock;
+
+use serde_json::Value as Schema;
+use toml::{Table, Value};
+
+use crate::album_config::album_config_schema;
+use crate::config::ConfigError;
+
+/// The album config's JSON Schema, which configs are checked against so the
+/// keys and types riptagger accepts are those of `AlbumConfig`.
+static SCHEMA: LazyLock<Schema> = LazyLock::new(album_config_schema);
+
+/// `schema` with its `$ref` followed, and the `anyOf` of one schema that an
+/// optional reference becomes unwrapped.
+fn resolve(schema: &'static Schema) -> &'static Schema {
+    if let Some(reference) = schema.get("$ref").and_then(Schema::as_str) {
+        let name = reference.trim_start_matches("#/definitions/");
+        return resolve(&SCHEMA["definitions"][name]);
+    }
+    match schema.get("anyOf").and_then(Schema::as_array).map(Vec::as_slice) {
+        Some([only]) => resolve(only),
+        _ => schema,
+    }
+}
+
+/// The strings an enum's schema allows, sorted. `None` if it isn't an enum.
+fn allowed_strings(schema: &Schema) -> Option<Vec<&str>> {
+    let mut allowed = Vec::new();
+    for variant in schema.get("oneOf")?.as_array()? {
+        match (variant.get("const"), variant.get("enum")) {
+            (Some(value), _) => allowed.push(value.as_str()?),
+            (None, Some(values)) => {
+                for value in values.as_array()? {
+                    allowed.push(value.as_str()?);
+                }
+            }
+            (None, None) => return None,
+        }
+    }
+    allowed.sort_unstable();
+    Some(allowed)
+}
+
+/// The type `schema` describes, as in "year should be an integer".
+fn describe(schema: &'static Schema) -> String {
+    let schema = resolve(schema);
+    if let Some(allowed) = allowed_strings(schema) {
+        let quoted: Vec<String> = allowed.iter().map(|v| format!("\"{}\"", v)).collect();
+        return format!("one of {}", quoted.join(", "));
+    }
+    if let Some(variants) = schema.get("anyOf").and_then(Schema::as_array) {
+        let described: Vec<String> = variants.iter().map(describe).collect();
+        return described.join(" or ");
+    }
+    match schema.get("type").and_then(Schema::as_str) {
+        Some("string") => String::from("a string"),
+        Some("integer") => String::from("an integer"),
+        Some("boolean") => String::from("true or false"),
+        Some("object") => String::from("a table"),
+        Some("array") => {
+            let items = match resolve(&schema["items"]).get("type").and_then(Schema::as_str) {
+                Some("string") => "strings",
+                Some("integer") => "integers",
+                Some("object") => "tables",
+                _ => "values",
+            };
+            format!("an array of {}", items)
+        }
+        _ => String::from("a value"),
+    }
+}
+
+fn matches(schema: &'static Schema, value: &Value) -> bool {
+    let schema = resolve(schema);
+    if let Some(allowed) = allowed_strings(schema) {
+        return value.as_str().is_some_and(|s| allowed.contains(&s));
+    }
+    if let Some(variants) = schema.get("anyOf").and_then(Schema::as_array) {
+        return variants.iter().any(|variant| matches(variant, value));
+    }
+    match schema.get("type").and_then(Schema::as_str) {
+        Some("string") => value.is_str(),
+        Some("integer") => value.is_integer(),
+        Some("boolean") => value.is_bool(),
+        Some("object") => value.is_table(),
+        Some("array") => value.as_array().is_some_and(|a| a.iter().all(|item| matches(&schema["items"], item))),
+        _ => true,
+    }
+}
+
+/// Levenshtein distance, for suggesting a key close to a misspelt one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The key of `keys` closest to `name`, if it's close enough to be a typo.
+pub fn suggest<'a>(name: &str, keys: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    keys.into_iter()
+        .map(|key| (edit_distance(name, key), key))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Check `table` against the properties of the object schema `schema`,
+/// recursing into tables and arrays of tables.
+/// `prefix` locates `table` in the config, for error messages.
+fn check_table(table: &Table, schema: &'static Schema, prefix: &str, problems: &mut Vec<ConfigError>) {
+    let Some(properties) = schema.get("properties").and_then(Schema::as_object) else {
+        return;
+    };
+    for (name, value) in table {
+        let Some(property) = properties.get(name) else {
+            problems.push(ConfigError::UnknownKey {
+                key: format!("{}{}", prefix, name),
+                suggestion: suggest(name, properties.keys().map(String::as_str)).map(|s| format!("{}{}", prefix, s)),
+            });
+            continue;
+        };
+        if !matches(property, value) {
+            problems.push(ConfigError::TypeError {
+                key: format!("{}{}", prefix, name),
+                message: format!("{}{} should be {}", prefix, name, describe(property)),
+            });
+            continue;
+        }
+        let property = resolve(property);
+        match value {
+            Value::Array(items) => {
+                for (i, nested) in items.iter().filter_map(Value::as_table).enumerate() {
+                    check_table(nested, resolve(&property["items"]), &format!("{}{}[{}].", prefix, name, i), problems);
+                }
+            }
+            Value::Table(nested) => {
+                check_table(nested, property, &format!("{}{}.", prefix, name), problems);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// `disc_total` and `tracks_per_disc` must agree with each other and with `tracks`.
+fn check_disc_layout(table: &Table, problems: &mut Vec<ConfigError>) {
+    let disc_total = table.get("disc_total").and_then(Value::as_integer);
+    let tracks_per_disc: Option<Vec<i64>> = table
+        .get("tracks_per_disc")
+        .and_then(Value::as_array)
+        .and_then(|a| a.iter().map(Value::as_integer).collect());
+    // tracks are listed either as a `tracks` array or as [[track]] tables
+    let track_count = [("tracks", "tracks"), ("track", "[[track]]")]
+        .into_iter()
+        .find_map(|(key, name)| Some((name, table.get(key)?.as_array()?.len() as i64)));
+
+    if disc_total.is_some_and(|n| n < 1) {
+        problems.push(ConfigError::Inconsistent {
+            key: String::from("disc_total"),
+            message: String::from("disc_total should be at least 1"),
+        });
+    }
+    let Some(tracks_per_disc) = tracks_per_disc else {
+        return;
+    };
+    if tracks_per_disc.iter().any(|n| *n < 0) {
+        problems.push(ConfigError::Inconsistent {
+            key: String::from("tracks_per_disc"),
+            message: String::from("tracks_per_disc can't be negative"),
+        });
+        return;
+    }
+    if let Some(disc_total) = disc_total.filter(|n| *n != tracks_per_disc.len() as i64) {
+        problems.push(ConfigError::Inconsistent {
+            key: String::from("disc_total"),
+            message: format!("disc_total is {}, but tracks_per_disc lists {} discs", disc_total, tracks_per_disc.len()),
+        });
+    }
+    if let Some((tracks_name, track_count)) = track_count {
+        let sum: i64 = tracks_per_disc.iter().sum();
+        if sum != track_count {
+            problems.push(ConfigError::Inconsistent {
+                key: String::from("tracks_per_disc"),
+                message: format!("tracks_per_disc adds up to {} tracks, but {} lists {}", sum, tracks_name, track_count),
+            });
+        }
+    }
+}
+
+/// Placeholders of which a `file_name_template` needs one, to give each track its own name.
+pub const TRACK_PLACEHOLDERS: &[&str] = &["{number}", "{track}", "{position}"];
+
+/// A `file_name_template` must tell tracks apart and name a file in the album
+/// directory. `prefix` locates the settings table in the config.
+fn check_file_name_template(settings: &Table, prefix: &str, problems: &mut Vec<ConfigError>) {
+    let Some(template) = settings.get("file_name_template").and_then(Value::as_str) else {
+        return;
+    };
+    if !TRACK_PLACEHOLDERS.iter().any(|p| template.contains(p)) {
+        problems.push(ConfigError::TypeError {
+            key: format!("{}file_name_template", prefix),
+            message: format!(
+                "{}file_name_template should contain {}, so each track gets its own name",
+                prefix,
+                TRACK_PLACEHOLDERS.join(", ")
+            ),
+        });
+    }
+    if template.contains(['/', '\\']) {
+        problems.push(ConfigError::TypeError {
+            key: format!("{}file_name_template", prefix),
+            message: format!(
+                "{}file_name_template can't contain a path separator, tracks are renamed within the album directory",
+                prefix
+            ),
+        });
+    }
+}
+
+/// Check an album config for unknown keys, values of the wrong type and disc
+/// counts that don't add up. A single problem is returned as is, several as
+/// `ConfigError::Invalid`.
+pub fn validate_album_table(table: &Table) -> Result<(), ConfigError> {
+    let mut problems = Vec::new();
+    check_table(table, &SCHEMA, "", &mut problems);
+    check_disc_layout(table, &mut problems);
+    if let Some(settings) = table.get("settings").and_then(Value::as_table) {
+        check_file_name_template(settings, "settings.", &mut problems);
+    }
+    into_result(problems)
+}
+
+/// Check a user config for unknown keys, values of the wrong type and a bad `file_name_template`.
+pub fn validate_settings_table(table: &Table) -> Result<(), ConfigError> {
+    let mut problems = Vec::new();
+    check_table(table, resolve(&SCHEMA["properties"]["settings"]), "", &mut problems);
+    check_file_name_template(table, "", &mut problems);
+    into_result(problems)
+}
+
+/// A single problem as is, several as `ConfigError::Invalid`.
+fn into_result(mut problems: Vec<ConfigError>) -> Result<(), ConfigError> {
+    match problems.len() {
+        0 => Ok(()),
+        1 => Err(problems.remove(0)),
+        _ => Err(ConfigError::Invalid(problems)),
+    }
+}